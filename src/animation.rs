@@ -0,0 +1,166 @@
+use crate::parser::Image;
+
+/// The frames of a cursor at a single nominal size.
+///
+/// An XCursor file stores animation frames as separate images with the same
+/// nominal size, each shown for `delay` milliseconds before the next one;
+/// the animation loops after the last frame. Cursors that aren't animated
+/// simply have a single frame.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AnimatedCursor {
+    size: u32,
+    frames: Vec<Image>,
+    /// The sum of the delays of all frames, in milliseconds.
+    duration: u64,
+}
+
+impl AnimatedCursor {
+    /// Group images by their nominal size, sorted by ascending size. Within
+    /// a group, frames keep the order in which they were given.
+    pub fn group(images: Vec<Image>) -> Vec<AnimatedCursor> {
+        let mut groups: Vec<Vec<Image>> = Vec::new();
+        for image in images {
+            match groups.iter_mut().find(|group| group[0].size == image.size) {
+                Some(group) => group.push(image),
+                None => groups.push(vec![image]),
+            }
+        }
+
+        groups.sort_by_key(|group| group[0].size);
+        groups
+            .into_iter()
+            .filter_map(AnimatedCursor::from_frames)
+            .collect()
+    }
+
+    /// Build a cursor from the given frames, such as the ones returned by
+    /// `XcursorReader::load_images`. The nominal size is taken from the
+    /// first frame.
+    ///
+    /// Returns `None` if there are no frames.
+    pub fn from_frames(frames: Vec<Image>) -> Option<AnimatedCursor> {
+        let size = frames.first()?.size;
+        let duration = frames.iter().map(|frame| u64::from(frame.delay)).sum();
+
+        Some(AnimatedCursor {
+            size,
+            frames,
+            duration,
+        })
+    }
+
+    /// The nominal size of the cursor.
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// The frames of the cursor, in display order. There is always at
+    /// least one.
+    pub fn frames(&self) -> &[Image] {
+        &self.frames
+    }
+
+    /// Consume the cursor, returning its frames.
+    pub fn into_frames(self) -> Vec<Image> {
+        self.frames
+    }
+
+    /// Whether the cursor has more than one frame to cycle through.
+    pub fn is_animated(&self) -> bool {
+        self.frames.len() > 1 && self.duration > 0
+    }
+
+    /// The time it takes to show every frame once, in milliseconds.
+    pub fn total_duration(&self) -> u64 {
+        self.duration
+    }
+
+    /// The index of the frame to show after `elapsed_ms` milliseconds since
+    /// the animation started.
+    ///
+    /// Frames with a delay of zero are never shown while animating. If the
+    /// cursor isn't animated, this is always the first frame.
+    pub fn frame_index_at(&self, elapsed_ms: u64) -> usize {
+        self.frame_and_remaining(elapsed_ms).0
+    }
+
+    /// The frame to show after `elapsed_ms` milliseconds since the animation
+    /// started. See [`frame_index_at`](#method.frame_index_at).
+    pub fn frame_at(&self, elapsed_ms: u64) -> &Image {
+        &self.frames[self.frame_index_at(elapsed_ms)]
+    }
+
+    /// How many milliseconds the frame shown after `elapsed_ms` milliseconds
+    /// stays on screen, or `None` if the cursor isn't animated.
+    pub fn time_to_next_frame(&self, elapsed_ms: u64) -> Option<u64> {
+        if self.is_animated() {
+            Some(self.frame_and_remaining(elapsed_ms).1)
+        } else {
+            None
+        }
+    }
+
+    /// The implementation of frame lookup, returning the frame index and
+    /// the time left until the next frame.
+    fn frame_and_remaining(&self, elapsed_ms: u64) -> (usize, u64) {
+        if !self.is_animated() {
+            return (0, 0);
+        }
+
+        // Same algorithm as libwayland-cursor's `wl_cursor_frame_and_duration`.
+        let mut time = elapsed_ms % self.duration;
+        for (i, frame) in self.frames.iter().enumerate() {
+            let delay = u64::from(frame.delay);
+            if time < delay {
+                return (i, delay - time);
+            }
+            time -= delay;
+        }
+
+        unreachable!("the elapsed time is smaller than the total duration")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AnimatedCursor;
+    use crate::test_util::image;
+
+    #[test]
+    fn test_group() {
+        let images = vec![
+            image(32, 10),
+            image(24, 20),
+            image(32, 30),
+            image(24, 40),
+            image(16, 0),
+        ];
+
+        let cursors = AnimatedCursor::group(images);
+        let sizes: Vec<u32> = cursors.iter().map(|c| c.size()).collect();
+        assert_eq!(sizes, vec![16, 24, 32]);
+        assert_eq!(cursors[1].frames(), &[image(24, 20), image(24, 40)][..]);
+        assert_eq!(cursors[2].total_duration(), 40);
+        assert!(!cursors[0].is_animated());
+
+        assert!(AnimatedCursor::from_frames(vec![]).is_none());
+    }
+
+    #[test]
+    fn test_frame_at() {
+        let cursor =
+            AnimatedCursor::from_frames(vec![image(24, 10), image(24, 0), image(24, 20)]).unwrap();
+
+        assert_eq!(cursor.frame_index_at(0), 0);
+        assert_eq!(cursor.frame_index_at(9), 0);
+        assert_eq!(cursor.frame_index_at(10), 2);
+        assert_eq!(cursor.frame_index_at(29), 2);
+        assert_eq!(cursor.frame_index_at(30), 0);
+        assert_eq!(cursor.frame_index_at(3005), 0);
+        assert_eq!(cursor.time_to_next_frame(15), Some(15));
+
+        let still = AnimatedCursor::from_frames(vec![image(24, 0), image(24, 0)]).unwrap();
+        assert_eq!(still.frame_index_at(1234), 0);
+        assert_eq!(still.time_to_next_frame(1234), None);
+    }
+}
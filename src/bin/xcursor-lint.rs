@@ -0,0 +1,45 @@
+//! Check cursor themes for common mistakes.
+//!
+//! Usage: `xcursor-lint THEME_DIR...`, e.g.
+//! `xcursor-lint ~/.icons/MyTheme`. Inherited themes are looked up in the
+//! default search paths. Exits with status 1 if any problem was found.
+
+use std::env;
+use std::path::PathBuf;
+use std::process;
+
+use xcursor::default_search_paths;
+use xcursor::lint::lint_theme;
+
+fn main() {
+    let theme_dirs: Vec<PathBuf> = env::args_os().skip(1).map(PathBuf::from).collect();
+    if theme_dirs.is_empty() {
+        eprintln!("Usage: xcursor-lint THEME_DIR...");
+        process::exit(2);
+    }
+
+    let search_paths = default_search_paths();
+    let mut failed = false;
+
+    for theme_dir in &theme_dirs {
+        match lint_theme(theme_dir, &search_paths) {
+            Ok(issues) => {
+                if !issues.is_empty() {
+                    println!("{}:", theme_dir.display());
+                    failed = true;
+                }
+                for issue in &issues {
+                    println!("    {}", issue);
+                }
+            }
+            Err(error) => {
+                eprintln!("{}: {}", theme_dir.display(), error);
+                failed = true;
+            }
+        }
+    }
+
+    if failed {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,243 @@
+use crate::names;
+use crate::parser::Image;
+use crate::{icon_candidates, load_first_cursor, CursorTheme, CursorThemeIml, LoadError};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// The number of cursors kept by [`CursorCache::new`](struct.CursorCache.html#method.new).
+pub const DEFAULT_CAPACITY: usize = 32;
+
+/// A [`CursorTheme`](../struct.CursorTheme.html) remembering the results of
+/// its lookups.
+///
+/// The inheritance chain of the theme is resolved once, and the location of
+/// each cursor is looked up once, including the cursors that aren't found.
+/// The most recently loaded cursors are kept in memory, keyed by name and
+/// requested size. Once warm, lookups don't touch the filesystem at all.
+///
+/// The cache never notices changes to the theme directories by itself; call
+/// [`clear`](#method.clear) to start over.
+#[derive(Debug, Clone)]
+pub struct CursorCache {
+    theme: CursorTheme,
+    /// The theme chain, resolved on first use.
+    chain: Option<Vec<(CursorThemeIml, usize)>>,
+    /// The files of each looked up cursor, with their inheritance depth.
+    paths: HashMap<String, Vec<(PathBuf, usize)>>,
+    /// The files of each cursor looked up with its aliases, with their inheritance depth.
+    alias_paths: HashMap<String, Vec<(PathBuf, usize)>>,
+    /// The loaded cursors, least recently used first.
+    images: Vec<Entry>,
+    capacity: usize,
+}
+
+/// A loaded cursor.
+#[derive(Debug, Clone)]
+struct Entry {
+    name: String,
+    size: u32,
+    /// Whether the cursor was looked up with its aliases.
+    aliases: bool,
+    images: Arc<[Image]>,
+}
+
+impl CursorCache {
+    /// Wrap a theme, keeping up to [`DEFAULT_CAPACITY`](constant.DEFAULT_CAPACITY.html)
+    /// loaded cursors.
+    pub fn new(theme: CursorTheme) -> Self {
+        Self::with_capacity(theme, DEFAULT_CAPACITY)
+    }
+
+    /// Wrap a theme, keeping up to `capacity` loaded cursors. A capacity of
+    /// zero only caches the cursor locations.
+    pub fn with_capacity(theme: CursorTheme, capacity: usize) -> Self {
+        CursorCache {
+            theme,
+            chain: None,
+            paths: HashMap::new(),
+            alias_paths: HashMap::new(),
+            images: Vec::new(),
+            capacity,
+        }
+    }
+
+    /// The wrapped theme.
+    pub fn theme(&self) -> &CursorTheme {
+        &self.theme
+    }
+
+    /// Cached version of [`CursorTheme::load_icon`](../struct.CursorTheme.html#method.load_icon).
+    pub fn load_icon(&mut self, icon_name: &str) -> Option<PathBuf> {
+        self.load_icon_with_depth(icon_name)
+            .map(|(pathbuf, _)| pathbuf)
+    }
+
+    /// Cached version of
+    /// [`CursorTheme::load_icon_with_depth`](../struct.CursorTheme.html#method.load_icon_with_depth).
+    pub fn load_icon_with_depth(&mut self, icon_name: &str) -> Option<(PathBuf, usize)> {
+        self.candidates(icon_name, false).first().cloned()
+    }
+
+    /// Cached version of
+    /// [`CursorTheme::load_icon_with_aliases`](../struct.CursorTheme.html#method.load_icon_with_aliases).
+    pub fn load_icon_with_aliases(&mut self, name: &str) -> Option<PathBuf> {
+        self.candidates(name, true)
+            .first()
+            .map(|(pathbuf, _)| pathbuf.clone())
+    }
+
+    /// Cached version of
+    /// [`CursorTheme::load_cursor`](../struct.CursorTheme.html#method.load_cursor).
+    ///
+    /// Errors aren't cached, so a cursor that failed to load is tried again
+    /// on the next call.
+    pub fn load_cursor(&mut self, name: &str, size: u32) -> Result<Arc<[Image]>, LoadError> {
+        self.load_cached_cursor(name, size, false)
+    }
+
+    /// Cached version of
+    /// [`CursorTheme::load_cursor_with_aliases`](../struct.CursorTheme.html#method.load_cursor_with_aliases).
+    pub fn load_cursor_with_aliases(
+        &mut self,
+        name: &str,
+        size: u32,
+    ) -> Result<Arc<[Image]>, LoadError> {
+        self.load_cached_cursor(name, size, true)
+    }
+
+    /// The implementation of cursor loading.
+    fn load_cached_cursor(
+        &mut self,
+        name: &str,
+        size: u32,
+        aliases: bool,
+    ) -> Result<Arc<[Image]>, LoadError> {
+        if let Some(position) = self
+            .images
+            .iter()
+            .position(|entry| entry.name == name && entry.size == size && entry.aliases == aliases)
+        {
+            // Move the entry to the most recently used end.
+            let entry = self.images.remove(position);
+            let images = entry.images.clone();
+            self.images.push(entry);
+            return Ok(images);
+        }
+
+        let candidates = self
+            .candidates(name, aliases)
+            .iter()
+            .map(|candidate| &candidate.0);
+        let images: Arc<[Image]> = load_first_cursor(name, candidates, size)?.into();
+
+        if self.capacity > 0 {
+            if self.images.len() >= self.capacity {
+                self.images.remove(0);
+            }
+            self.images.push(Entry {
+                name: name.to_owned(),
+                size,
+                aliases,
+                images: images.clone(),
+            });
+        }
+        Ok(images)
+    }
+
+    /// Forget everything, including the directories of the theme itself, so
+    /// that the next lookups see the current state of the filesystem.
+    pub fn clear(&mut self) {
+        self.theme.theme = CursorThemeIml::load(&self.theme.theme.name, &self.theme.search_paths);
+        self.chain = None;
+        self.paths.clear();
+        self.alias_paths.clear();
+        self.images.clear();
+    }
+
+    /// The files of a cursor, optionally with its aliases, looking them up if needed.
+    fn candidates(&mut self, icon_name: &str, aliases: bool) -> &[(PathBuf, usize)] {
+        let paths = if aliases {
+            &mut self.alias_paths
+        } else {
+            &mut self.paths
+        };
+
+        if !paths.contains_key(icon_name) {
+            let theme = &self.theme;
+            let chain = self
+                .chain
+                .get_or_insert_with(|| theme.theme.chain(&theme.search_paths).collect());
+            let names = if aliases {
+                names::lookup_names(icon_name)
+            } else {
+                vec![icon_name]
+            };
+            let candidates = icon_candidates(chain.iter().cloned(), &names).collect();
+            paths.insert(icon_name.to_owned(), candidates);
+        }
+
+        &paths[icon_name]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CursorCache;
+    use crate::test_util::{image, write_cursor, TempDir};
+    use crate::{CursorTheme, LoadError};
+    use std::fs;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_cache() {
+        let dir = TempDir::new("cache");
+        let theme_dir = dir.add_theme("theme", Some("Inherits=none"), &[]);
+        let cursors = theme_dir.join("cursors");
+        write_cursor(&cursors.join("x"), &[image(24, 0)]);
+        write_cursor(&cursors.join("y"), &[image(24, 0)]);
+
+        let theme = CursorTheme::load_with_search_paths("theme", vec![dir.0.clone()]);
+        let mut cache = CursorCache::with_capacity(theme, 1);
+
+        assert_eq!(cache.load_icon("z"), None);
+        let x = cache.load_cursor("x", 24).unwrap();
+        assert_eq!(x[0].size, 24);
+
+        // Served from memory, even though the files changed.
+        fs::remove_file(cursors.join("x")).unwrap();
+        write_cursor(&cursors.join("z"), &[image(24, 0)]);
+        assert!(Arc::ptr_eq(&x, &cache.load_cursor("x", 24).unwrap()));
+        assert_eq!(cache.load_icon("z"), None);
+
+        // Loading `y` evicts `x`, whose file is gone.
+        cache.load_cursor("y", 24).unwrap();
+        match cache.load_cursor("x", 24) {
+            Err(LoadError::Io { .. }) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        // Aliases are cached separately from exact lookups.
+        assert_eq!(cache.load_icon_with_aliases("text"), None);
+        write_cursor(&cursors.join("xterm"), &[image(24, 0)]);
+        assert_eq!(cache.load_icon_with_aliases("text"), None);
+
+        cache.clear();
+        assert_eq!(
+            cache.load_icon_with_aliases("text"),
+            Some(cursors.join("xterm"))
+        );
+        let xterm = cache.load_cursor_with_aliases("text", 24).unwrap();
+        fs::remove_file(cursors.join("xterm")).unwrap();
+        assert!(Arc::ptr_eq(
+            &xterm,
+            &cache.load_cursor_with_aliases("text", 24).unwrap()
+        ));
+        assert_eq!(cache.load_icon("text"), None);
+        assert_eq!(cache.load_icon("z"), Some(cursors.join("z")));
+        match cache.load_cursor("x", 24) {
+            Err(LoadError::NotFound { .. }) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+}
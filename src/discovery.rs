@@ -0,0 +1,171 @@
+use crate::default_search_paths;
+use crate::theme_index::ThemeIndex;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A theme found in the search paths.
+///
+/// The same theme can be presented in several search paths, e.g. in both
+/// `~/.icons` and `/usr/share/icons`; all of them are listed, in the order
+/// they are searched in.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct InstalledTheme {
+    name: String,
+    directories: Vec<ThemeDirectory>,
+    index: Option<ThemeIndex>,
+}
+
+/// A directory where a theme is presented.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ThemeDirectory {
+    /// The path of the theme directory, like `/usr/share/icons/Adwaita`.
+    pub path: PathBuf,
+    /// Whether the directory has a `cursors` subdirectory.
+    pub has_cursors: bool,
+}
+
+impl InstalledTheme {
+    /// The name of the theme, which is the name of its directories, as
+    /// passed to [`CursorTheme::load`](../struct.CursorTheme.html#method.load).
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The directories where the theme is presented, in search order.
+    pub fn directories(&self) -> &[ThemeDirectory] {
+        &self.directories
+    }
+
+    /// The `index.theme` file of the theme, from the first directory that
+    /// has one.
+    pub fn index(&self) -> Option<&ThemeIndex> {
+        self.index.as_ref()
+    }
+
+    /// The display name of the theme, falling back to its directory name.
+    pub fn display_name(&self) -> &str {
+        self.index
+            .as_ref()
+            .and_then(ThemeIndex::name)
+            .unwrap_or(&self.name)
+    }
+
+    /// Whether the theme provides cursors of its own, i.e. any of its
+    /// directories has a `cursors` subdirectory. Themes which only provide
+    /// icons return `false`.
+    pub fn is_cursor_theme(&self) -> bool {
+        self.directories.iter().any(|dir| dir.has_cursors)
+    }
+}
+
+/// List the themes installed in the default search paths, see
+/// [`default_search_paths`](../fn.default_search_paths.html).
+pub fn installed_themes() -> Vec<InstalledTheme> {
+    installed_themes_in(&default_search_paths())
+}
+
+/// List the themes installed in the given search paths, sorted by name.
+///
+/// A theme is any directory with either an `index.theme` file or a
+/// `cursors` subdirectory. Directories which cannot be read are ignored.
+pub fn installed_themes_in(search_paths: &[PathBuf]) -> Vec<InstalledTheme> {
+    let mut themes: Vec<InstalledTheme> = Vec::new();
+
+    for search_path in search_paths {
+        let entries = match fs::read_dir(search_path) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+
+        let mut found: Vec<(String, PathBuf)> = entries
+            .filter_map(|entry| {
+                let entry = entry.ok()?;
+                let name = entry.file_name().into_string().ok()?;
+                Some((name, entry.path()))
+            })
+            .collect();
+        found.sort();
+
+        for (name, path) in found {
+            let has_cursors = path.join("cursors").is_dir();
+            let index_path = path.join("index.theme");
+            if !path.is_dir() || !(has_cursors || index_path.is_file()) {
+                continue;
+            }
+
+            let position = match themes.iter().position(|theme| theme.name == name) {
+                Some(position) => position,
+                None => {
+                    themes.push(InstalledTheme {
+                        name,
+                        directories: Vec::new(),
+                        index: None,
+                    });
+                    themes.len() - 1
+                }
+            };
+
+            let theme = &mut themes[position];
+            if theme.index.is_none() {
+                theme.index = load_index(&index_path);
+            }
+            theme.directories.push(ThemeDirectory { path, has_cursors });
+        }
+    }
+
+    themes.sort_by(|a, b| a.name.cmp(&b.name));
+    themes
+}
+
+fn load_index(path: &Path) -> Option<ThemeIndex> {
+    if path.is_file() {
+        ThemeIndex::load(path).ok()
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{installed_themes_in, ThemeDirectory};
+    use crate::test_util::TempDir;
+    use std::fs;
+
+    #[test]
+    fn test_installed_themes() {
+        let user = TempDir::new("installed-themes-user");
+        let system = TempDir::new("installed-themes-system");
+        let user_breeze = user.add_theme("breeze", None, &["left_ptr"]);
+        let system_breeze = system.add_icon_theme("breeze", "[Icon Theme]\nName=Breeze");
+        let hicolor = system.add_icon_theme("hicolor", "[Icon Theme]\nName=Hicolor");
+        fs::create_dir(system.0.join("not-a-theme")).unwrap();
+        fs::write(system.0.join("file"), b"").unwrap();
+
+        let search_paths = vec![user.0.clone(), user.0.join("missing"), system.0.clone()];
+        let themes = installed_themes_in(&search_paths);
+
+        let names: Vec<&str> = themes.iter().map(|theme| theme.name()).collect();
+        assert_eq!(names, vec!["breeze", "hicolor"]);
+
+        assert_eq!(
+            themes[0].directories(),
+            &[
+                ThemeDirectory {
+                    path: user_breeze,
+                    has_cursors: true,
+                },
+                ThemeDirectory {
+                    path: system_breeze,
+                    has_cursors: false,
+                },
+            ][..]
+        );
+        assert!(themes[0].is_cursor_theme());
+        assert_eq!(themes[0].display_name(), "Breeze");
+
+        assert_eq!(themes[1].directories()[0].path, hicolor);
+        assert!(!themes[1].is_cursor_theme());
+
+        assert!(installed_themes_in(&[]).is_empty());
+    }
+}
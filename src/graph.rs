@@ -0,0 +1,258 @@
+use crate::{CursorTheme, CursorThemeIml};
+use std::collections::HashSet;
+use std::fmt;
+use std::path::PathBuf;
+
+/// The inheritance graph of a theme: the theme, and every theme it
+/// inherits from, directly or not.
+///
+/// Unlike cursor lookups, which silently skip themes that were already
+/// searched and parents that aren't installed, the graph records these
+/// problems as [`GraphIssue`](enum.GraphIssue.html)s.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ThemeGraph {
+    nodes: Vec<ThemeNode>,
+    issues: Vec<GraphIssue>,
+}
+
+/// A theme of an inheritance graph.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ThemeNode {
+    /// The name of the theme.
+    pub name: String,
+    /// The directories of the theme. Empty if it is not installed in any
+    /// search path.
+    pub directories: Vec<PathBuf>,
+    /// The themes it inherits from, in order, merged from all of its
+    /// directories.
+    pub inherits: Vec<String>,
+    /// Whether `inherits` only holds the `default` theme because no
+    /// `index.theme` file of the theme has an `Inherits` key.
+    pub implicit_default: bool,
+}
+
+/// A problem found in an inheritance graph.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum GraphIssue {
+    /// The theme whose graph was built is not installed in any search path.
+    NotInstalled { theme: String },
+    /// A theme lists itself in its `Inherits` key.
+    SelfInheritance { theme: String },
+    /// A theme lists a parent which is not installed in any search path.
+    /// Only reported for parents named in an `Inherits` key.
+    MissingParent { theme: String, parent: String },
+    /// Following the `Inherits` keys leads back to a theme. The themes of
+    /// the cycle are listed in order, starting with the one that was
+    /// reached twice.
+    Cycle { themes: Vec<String> },
+}
+
+impl fmt::Display for GraphIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            GraphIssue::NotInstalled { ref theme } => {
+                write!(f, "Theme {:?} is not installed", theme)
+            }
+            GraphIssue::SelfInheritance { ref theme } => {
+                write!(f, "Theme {:?} inherits from itself", theme)
+            }
+            GraphIssue::MissingParent {
+                ref theme,
+                ref parent,
+            } => write!(
+                f,
+                "Theme {:?} inherits from {:?}, which is not installed",
+                theme, parent
+            ),
+            GraphIssue::Cycle { ref themes } => match themes.first() {
+                Some(first) => write!(f, "Inheritance cycle: {} -> {}", themes.join(" -> "), first),
+                None => write!(f, "Inheritance cycle"),
+            },
+        }
+    }
+}
+
+impl ThemeGraph {
+    /// The themes of the graph, in the order cursors are searched in them.
+    /// The first one is the theme the graph was built for.
+    pub fn nodes(&self) -> &[ThemeNode] {
+        &self.nodes
+    }
+
+    /// Get a theme of the graph by name.
+    pub fn node(&self, name: &str) -> Option<&ThemeNode> {
+        self.nodes.iter().find(|node| node.name == name)
+    }
+
+    /// The problems found in the graph, in the order they were found.
+    pub fn issues(&self) -> &[GraphIssue] {
+        &self.issues
+    }
+
+    /// Whether the graph has no problems.
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Walk the graph depth-first from the given theme.
+    fn visit(
+        &mut self,
+        name: &str,
+        search_paths: &[PathBuf],
+        stack: &mut Vec<String>,
+        done: &mut HashSet<String>,
+    ) {
+        if let Some(position) = stack.iter().position(|theme| theme == name) {
+            self.issues.push(GraphIssue::Cycle {
+                themes: stack[position..].to_vec(),
+            });
+            return;
+        }
+        if done.contains(name) {
+            return;
+        }
+
+        let (node, explicit) = load_node(name, search_paths);
+        let inherits = node.inherits.clone();
+        self.nodes.push(node);
+        stack.push(name.to_owned());
+
+        for parent in &inherits {
+            if parent == name {
+                self.issues.push(GraphIssue::SelfInheritance {
+                    theme: name.to_owned(),
+                });
+                continue;
+            }
+            if explicit.contains(parent) && !is_installed(parent, search_paths) {
+                self.issues.push(GraphIssue::MissingParent {
+                    theme: name.to_owned(),
+                    parent: parent.clone(),
+                });
+            }
+
+            self.visit(parent, search_paths, stack, done);
+        }
+
+        stack.pop();
+        done.insert(name.to_owned());
+    }
+}
+
+impl CursorTheme {
+    /// Build the inheritance graph of the theme, reading the `index.theme`
+    /// files of all the themes it inherits from.
+    pub fn inheritance_graph(&self) -> ThemeGraph {
+        let name = self.theme.name.clone();
+        let mut graph = ThemeGraph {
+            nodes: Vec::new(),
+            issues: Vec::new(),
+        };
+
+        if self.theme.data.is_empty() {
+            graph.issues.push(GraphIssue::NotInstalled {
+                theme: name.clone(),
+            });
+        }
+        graph.visit(
+            &name,
+            &self.search_paths,
+            &mut Vec::new(),
+            &mut HashSet::new(),
+        );
+
+        graph
+    }
+}
+
+/// Load a theme of the graph, along with the parents named in its `Inherits` keys.
+fn load_node(name: &str, search_paths: &[PathBuf]) -> (ThemeNode, Vec<String>) {
+    let theme = CursorThemeIml::load(name, search_paths);
+
+    let mut inherits: Vec<String> = Vec::new();
+    let mut explicit: Vec<String> = Vec::new();
+    for data in &theme.data {
+        for parent in &data.1 {
+            if !inherits.contains(parent) {
+                inherits.push(parent.clone());
+            }
+            if !data.2 && !explicit.contains(parent) {
+                explicit.push(parent.clone());
+            }
+        }
+    }
+
+    let node = ThemeNode {
+        name: name.to_owned(),
+        implicit_default: !theme.data.is_empty() && theme.data.iter().all(|data| data.2),
+        directories: theme.data.into_iter().map(|data| data.0).collect(),
+        inherits,
+    };
+    (node, explicit)
+}
+
+fn is_installed(name: &str, search_paths: &[PathBuf]) -> bool {
+    search_paths.iter().any(|path| path.join(name).is_dir())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GraphIssue;
+    use crate::test_util::TempDir;
+    use crate::CursorTheme;
+
+    #[test]
+    fn test_inheritance_graph() {
+        let dir = TempDir::new("inheritance-graph");
+        dir.add_theme("root", Some("Inherits=a, root, ghost"), &[]);
+        dir.add_theme("a", Some("Inherits=b, c"), &[]);
+        dir.add_theme("b", Some("Inherits=a"), &[]);
+        dir.add_theme("c", None, &[]);
+
+        let theme = CursorTheme::load_with_search_paths("root", vec![dir.0.clone()]);
+        let graph = theme.inheritance_graph();
+
+        let names: Vec<&str> = graph.nodes().iter().map(|node| &node.name[..]).collect();
+        assert_eq!(names, vec!["root", "a", "b", "c", "default", "ghost"]);
+        assert_eq!(graph.node("a").unwrap().inherits, vec!["b", "c"]);
+        assert!(graph.node("c").unwrap().implicit_default);
+        assert!(!graph.node("root").unwrap().implicit_default);
+        assert!(graph.node("ghost").unwrap().directories.is_empty());
+
+        assert_eq!(
+            graph.issues(),
+            &[
+                GraphIssue::Cycle {
+                    themes: vec![String::from("a"), String::from("b")],
+                },
+                GraphIssue::SelfInheritance {
+                    theme: String::from("root"),
+                },
+                GraphIssue::MissingParent {
+                    theme: String::from("root"),
+                    parent: String::from("ghost"),
+                },
+            ][..]
+        );
+        assert_eq!(
+            graph.issues()[0].to_string(),
+            "Inheritance cycle: a -> b -> a"
+        );
+
+        let empty = GraphIssue::Cycle { themes: vec![] };
+        assert_eq!(empty.to_string(), "Inheritance cycle");
+
+        let graph =
+            CursorTheme::load_with_search_paths("c", vec![dir.0.clone()]).inheritance_graph();
+        assert!(graph.is_valid());
+
+        let graph =
+            CursorTheme::load_with_search_paths("ghost", vec![dir.0.clone()]).inheritance_graph();
+        assert_eq!(
+            graph.issues(),
+            &[GraphIssue::NotInstalled {
+                theme: String::from("ghost"),
+            }][..]
+        );
+    }
+}
@@ -2,10 +2,38 @@
 
 use std::collections::HashSet;
 use std::env;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader};
 use std::path::{Path, PathBuf};
 
+use crate::parser::{Image, ParseError, XcursorReader};
+
+/// A module grouping XCursor images into animated cursors.
+pub mod animation;
+/// A module caching theme lookups and parsed cursors.
+pub mod cache;
+/// A module listing the themes installed in the search paths.
+pub mod discovery;
+/// A module inspecting the inheritance graph of themes.
+pub mod graph;
+/// A module checking themes for common mistakes.
+pub mod lint;
+/// A module mapping standard cursor names to the names used by themes.
+pub mod names;
 /// A module implementing XCursor file parsing.
 pub mod parser;
+/// A module explaining how cursors are resolved.
+pub mod resolve;
+/// A module reading the cursor theme and size configured by the user.
+pub mod settings;
+/// A module implementing `index.theme` file parsing.
+pub mod theme_index;
+/// A module detecting changes to installed themes.
+pub mod watch;
+
+#[cfg(test)]
+mod test_util;
 
 /// A cursor theme.
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -20,8 +48,13 @@ impl CursorTheme {
     /// and returns an XCursorTheme which represents it. If no inheritance
     /// can be determined, then the themes inherits from the "default" theme.
     pub fn load(name: &str) -> Self {
-        let search_paths = theme_search_paths(SearchPathsEnvironment::get());
+        Self::load_with_search_paths(name, default_search_paths())
+    }
 
+    /// Search for a theme with the given name in the given search paths only,
+    /// ignoring the environment. Inherited themes are searched in the same
+    /// paths.
+    pub fn load_with_search_paths(name: &str, search_paths: Vec<PathBuf>) -> Self {
         let theme = CursorThemeIml::load(name, &search_paths);
 
         CursorTheme {
@@ -30,15 +63,42 @@ impl CursorTheme {
         }
     }
 
+    /// Load the theme with the given name from a single directory, ignoring the other
+    /// directories it may be installed in. Inherited themes are searched in `search_paths`.
+    pub(crate) fn load_from_directory(
+        name: &str,
+        theme_dir: &Path,
+        search_paths: Vec<PathBuf>,
+    ) -> Self {
+        let theme = CursorThemeIml {
+            name: name.to_owned(),
+            data: vec![CursorThemeIml::load_directory(name, theme_dir.to_owned())],
+        };
+
+        CursorTheme {
+            theme,
+            search_paths,
+        }
+    }
+
+    /// Start building a theme with the given name, to customize the paths
+    /// it is searched in.
+    pub fn builder(name: &str) -> CursorThemeBuilder {
+        CursorThemeBuilder::new(name)
+    }
+
+    /// The paths the theme and its inherited themes are searched in, in
+    /// order of priority.
+    pub fn search_paths(&self) -> &[PathBuf] {
+        &self.search_paths
+    }
+
     /// Try to load an icon from the theme.
     /// If the icon is not found within this theme's
     /// directories, then the function looks at the
     /// theme from which this theme is inherited.
     pub fn load_icon(&self, icon_name: &str) -> Option<PathBuf> {
-        let mut walked_themes = HashSet::new();
-
-        self.theme
-            .load_icon_with_depth(icon_name, &self.search_paths, &mut walked_themes)
+        self.load_icon_with_depth(icon_name)
             .map(|(pathbuf, _)| pathbuf)
     }
 
@@ -50,10 +110,277 @@ impl CursorTheme {
     /// second element of the returned tuple indicates how many levels of
     /// inheritance were traversed before the icon was found.
     pub fn load_icon_with_depth(&self, icon_name: &str) -> Option<(PathBuf, usize)> {
-        let mut walked_themes = HashSet::new();
-
         self.theme
-            .load_icon_with_depth(icon_name, &self.search_paths, &mut walked_themes)
+            .chain(&self.search_paths)
+            .find_map(|(theme, depth)| theme.find_icon(icon_name).map(|path| (path, depth)))
+    }
+
+    /// Try to load an icon from the theme, also trying the other names of
+    /// the cursor if `name` is a CSS cursor name, like `pointer` or
+    /// `ns-resize`. See [`names::aliases`](names/fn.aliases.html).
+    ///
+    /// All the names are tried in each theme before looking at the theme it
+    /// inherits from, so that a theme's own legacy cursor is preferred to an
+    /// inherited one with the CSS name.
+    pub fn load_icon_with_aliases(&self, name: &str) -> Option<PathBuf> {
+        let chain = self.theme.chain(&self.search_paths);
+
+        icon_candidates(chain, &names::lookup_names(name))
+            .next()
+            .map(|(pathbuf, _)| pathbuf)
+    }
+
+    /// Load the images of a cursor, selecting the nominal size closest to
+    /// `size` like libXcursor does. Animated cursors return all their
+    /// frames, in order.
+    ///
+    /// The cursor files are tried in the same order as
+    /// [`load_icon`](#method.load_icon) uses, moving on to the next one if a
+    /// file cannot be read, cannot be parsed or contains no images. If none
+    /// of them can be loaded, the error of the first one is returned.
+    pub fn load_cursor(&self, name: &str, size: u32) -> Result<Vec<Image>, LoadError> {
+        let chain = self.theme.chain(&self.search_paths);
+        let names = [name];
+        let candidates = icon_candidates(chain, &names).map(|(path, _)| path);
+
+        load_first_cursor(name, candidates, size)
+    }
+
+    /// Load the images of a cursor like [`load_cursor`](#method.load_cursor),
+    /// trying its other names like
+    /// [`load_icon_with_aliases`](#method.load_icon_with_aliases).
+    pub fn load_cursor_with_aliases(&self, name: &str, size: u32) -> Result<Vec<Image>, LoadError> {
+        let chain = self.theme.chain(&self.search_paths);
+        let names = names::lookup_names(name);
+        let candidates = icon_candidates(chain, &names).map(|(path, _)| path);
+
+        load_first_cursor(name, candidates, size)
+    }
+
+    /// List every cursor the theme resolves, including the ones provided
+    /// by inherited themes, sorted by name.
+    ///
+    /// Each cursor is reported once, from the theme that
+    /// [`load_icon`](#method.load_icon) would load it from.
+    pub fn cursors(&self) -> impl Iterator<Item = ThemeCursor> {
+        let mut cursors: Vec<ThemeCursor> = Vec::new();
+        let mut seen = HashSet::new();
+
+        for (theme, depth) in self.theme.chain(&self.search_paths) {
+            for data in &theme.data {
+                let entries = match std::fs::read_dir(data.0.join("cursors")) {
+                    Ok(entries) => entries,
+                    Err(_) => continue,
+                };
+
+                for entry in entries.filter_map(Result::ok) {
+                    let path = entry.path();
+                    let name = match entry.file_name().into_string() {
+                        Ok(name) => name,
+                        Err(_) => continue,
+                    };
+                    if !path.is_file() || !seen.insert(name.clone()) {
+                        continue;
+                    }
+
+                    cursors.push(ThemeCursor {
+                        name,
+                        theme: theme.name.clone(),
+                        path,
+                        depth,
+                    });
+                }
+            }
+        }
+
+        cursors.sort_by(|a, b| a.name.cmp(&b.name));
+        cursors.into_iter()
+    }
+}
+
+/// A cursor resolved by a [`CursorTheme`](struct.CursorTheme.html).
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ThemeCursor {
+    /// The name of the cursor, like `left_ptr`.
+    pub name: String,
+    /// The name of the theme providing the cursor.
+    pub theme: String,
+    /// The path of the cursor file.
+    pub path: PathBuf,
+    /// How many levels of inheritance were traversed to find the cursor,
+    /// 0 if the theme provides it itself.
+    pub depth: usize,
+}
+
+/// An error returned by
+/// [`CursorTheme::load_cursor`](struct.CursorTheme.html#method.load_cursor).
+#[derive(Debug)]
+pub enum LoadError {
+    /// No theme in the inheritance chain provides the cursor.
+    NotFound {
+        /// The name of the cursor.
+        name: String,
+    },
+
+    /// The cursor file could not be opened.
+    Io { path: PathBuf, error: io::Error },
+
+    /// The cursor file is not a valid XCursor file.
+    Parse { path: PathBuf, error: ParseError },
+
+    /// The cursor file doesn't contain any image.
+    NoImages { path: PathBuf },
+}
+
+impl LoadError {
+    /// The path of the cursor file that failed to load, if one was found.
+    pub fn path(&self) -> Option<&Path> {
+        match *self {
+            LoadError::NotFound { .. } => None,
+            LoadError::Io { ref path, .. }
+            | LoadError::Parse { ref path, .. }
+            | LoadError::NoImages { ref path } => Some(path),
+        }
+    }
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            LoadError::NotFound { ref name } => write!(f, "Cursor {:?} not found", name),
+            LoadError::Io {
+                ref path,
+                ref error,
+            } => write!(f, "Failed to read {}: {}", path.display(), error),
+            LoadError::Parse {
+                ref path,
+                ref error,
+            } => write!(f, "Failed to parse {}: {}", path.display(), error),
+            LoadError::NoImages { ref path } => {
+                write!(f, "No images in {}", path.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            LoadError::Io { ref error, .. } => Some(error),
+            LoadError::Parse { ref error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Find the files of an icon along a theme chain, with their inheritance depth, in search order.
+/// Each theme is searched for all the given names, in order, before moving on to the next one.
+///
+/// The chain is only walked as far as the returned iterator is consumed.
+fn icon_candidates<'a, I>(
+    chain: I,
+    icon_names: &'a [&'a str],
+) -> impl Iterator<Item = (PathBuf, usize)> + 'a
+where
+    I: IntoIterator<Item = (CursorThemeIml, usize)>,
+    I::IntoIter: 'a,
+{
+    chain.into_iter().flat_map(move |(theme, depth)| {
+        icon_names.iter().flat_map(move |icon_name| {
+            theme
+                .icon_paths(icon_name)
+                .into_iter()
+                .map(move |path| (path, depth))
+        })
+    })
+}
+
+/// Read the images of the given size from the first of the candidate files that can be loaded.
+/// If none can, returns the error of the first one.
+fn load_first_cursor<I>(name: &str, candidates: I, size: u32) -> Result<Vec<Image>, LoadError>
+where
+    I: IntoIterator,
+    I::Item: AsRef<Path>,
+{
+    let mut first_error = None;
+
+    for path in candidates {
+        match load_cursor_file(path.as_ref(), size) {
+            Ok(images) => return Ok(images),
+            Err(error) => {
+                first_error.get_or_insert(error);
+            }
+        }
+    }
+
+    Err(first_error.unwrap_or_else(|| LoadError::NotFound {
+        name: name.to_owned(),
+    }))
+}
+
+/// Read the images of the given size from a cursor file.
+fn load_cursor_file(path: &Path, size: u32) -> Result<Vec<Image>, LoadError> {
+    let file = File::open(path).map_err(|error| LoadError::Io {
+        path: path.to_owned(),
+        error,
+    })?;
+
+    let parse_error = |error| LoadError::Parse {
+        path: path.to_owned(),
+        error,
+    };
+    let mut reader = XcursorReader::new(BufReader::new(file)).map_err(parse_error)?;
+    let images = reader.load_images(size).map_err(parse_error)?;
+
+    if images.is_empty() {
+        return Err(LoadError::NoImages {
+            path: path.to_owned(),
+        });
+    }
+    Ok(images)
+}
+
+/// A builder for a [`CursorTheme`](struct.CursorTheme.html) searched in
+/// custom paths.
+///
+/// The search paths start as the ones computed from the environment, see
+/// [`default_search_paths`](fn.default_search_paths.html).
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CursorThemeBuilder {
+    name: String,
+    search_paths: Vec<PathBuf>,
+}
+
+impl CursorThemeBuilder {
+    /// Create a builder for the theme with the given name.
+    pub fn new(name: &str) -> Self {
+        CursorThemeBuilder {
+            name: name.to_owned(),
+            search_paths: default_search_paths(),
+        }
+    }
+
+    /// Replace the search paths.
+    pub fn search_paths(mut self, search_paths: Vec<PathBuf>) -> Self {
+        self.search_paths = search_paths;
+        self
+    }
+
+    /// Add a path to search before all the others.
+    pub fn prepend_search_path<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.search_paths.insert(0, path.into());
+        self
+    }
+
+    /// Add a path to search after all the others.
+    pub fn append_search_path<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.search_paths.push(path.into());
+        self
+    }
+
+    /// Search for the theme in the configured paths.
+    pub fn build(self) -> CursorTheme {
+        CursorTheme::load_with_search_paths(&self.name, self.search_paths)
     }
 }
 
@@ -61,9 +388,10 @@ impl CursorTheme {
 struct CursorThemeIml {
     /// Theme name.
     name: String,
-    /// Directories where the theme is presented and corresponding names of inherited themes.
-    /// `None` if theme inherits nothing.
-    data: Vec<(PathBuf, Option<String>)>,
+    /// Directories where the theme is presented, corresponding names of inherited themes, in
+    /// order, and whether they only hold the implied "default" theme because the directory's
+    /// index.theme has no `Inherits` key. Names are empty if theme inherits nothing.
+    data: Vec<(PathBuf, Vec<String>, bool)>,
 }
 
 impl CursorThemeIml {
@@ -72,21 +400,10 @@ impl CursorThemeIml {
         let mut data = Vec::new();
 
         // Find directories where this theme is presented.
-        for mut path in search_paths.iter().cloned() {
-            path.push(name);
+        for path in search_paths {
+            let path = path.join(name);
             if path.is_dir() {
-                let data_dir = path.clone();
-
-                path.push("index.theme");
-                let inherits = if let Some(inherits) = theme_inherits(&path) {
-                    Some(inherits)
-                } else if name != "default" {
-                    Some(String::from("default"))
-                } else {
-                    None
-                };
-
-                data.push((data_dir, inherits));
+                data.push(Self::load_directory(name, path));
             }
         }
 
@@ -96,47 +413,103 @@ impl CursorThemeIml {
         }
     }
 
-    /// The implementation of cursor icon loading.
-    fn load_icon_with_depth(
-        &self,
-        icon_name: &str,
-        search_paths: &[PathBuf],
-        walked_themes: &mut HashSet<String>,
-    ) -> Option<(PathBuf, usize)> {
+    /// Read the inherited themes of one directory of the theme.
+    fn load_directory(name: &str, data_dir: PathBuf) -> (PathBuf, Vec<String>, bool) {
+        let mut inherits = theme_inherits(&data_dir.join("index.theme"));
+        let implicit_default = inherits.is_empty() && name != "default";
+        if implicit_default {
+            inherits.push(String::from("default"));
+        }
+
+        (data_dir, inherits, implicit_default)
+    }
+
+    /// Find an icon in the theme's own directories.
+    fn find_icon(&self, icon_name: &str) -> Option<PathBuf> {
+        self.icon_paths(icon_name).into_iter().next()
+    }
+
+    /// Find all the files of an icon in the theme's own directories, in search order.
+    fn icon_paths(&self, icon_name: &str) -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+
         for data in &self.data {
             let mut icon_path = data.0.clone();
             icon_path.push("cursors");
             icon_path.push(icon_name);
             if icon_path.is_file() {
-                return Some((icon_path, 0));
+                paths.push(icon_path);
             }
         }
 
-        // We've processed all based theme files. Traverse inherited themes, marking this theme
-        // as already visited to avoid infinite recursion.
+        paths
+    }
+
+    /// Walk this theme and all the themes it inherits from, with their inheritance depth, in the
+    /// order they are searched in. Inherited themes are only loaded when the walk reaches them.
+    fn chain<'a>(&self, search_paths: &'a [PathBuf]) -> Chain<'a> {
+        let mut walked_themes = HashSet::new();
         walked_themes.insert(self.name.clone());
 
-        for data in &self.data {
-            // Get inherited theme name, if any.
-            let inherits = match data.1.as_ref() {
+        Chain {
+            search_paths,
+            walked_themes,
+            root: Some(self.clone()),
+            stack: Vec::new(),
+        }
+    }
+
+    /// The names of the inherited themes, in reverse order, to be popped from a `Chain` stack.
+    fn parents_reversed(&self) -> Vec<String> {
+        let mut parents: Vec<String> = self.data.iter().flat_map(|data| data.1.clone()).collect();
+        parents.reverse();
+        parents
+    }
+}
+
+/// A lazy walk over a theme chain. Inherited themes are traversed depth-first, in the order they
+/// are listed, skipping already visited ones to avoid infinite recursion.
+struct Chain<'a> {
+    search_paths: &'a [PathBuf],
+    walked_themes: HashSet<String>,
+    /// The theme the chain starts from, until it is returned.
+    root: Option<CursorThemeIml>,
+    /// For each theme being walked, the names of its inherited themes left to walk, in reverse
+    /// order, and their depth.
+    stack: Vec<(Vec<String>, usize)>,
+}
+
+impl<'a> Iterator for Chain<'a> {
+    type Item = (CursorThemeIml, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(root) = self.root.take() {
+            self.stack.push((root.parents_reversed(), 1));
+            return Some((root, 0));
+        }
+
+        loop {
+            let (inherits, depth) = match self.stack.last_mut() {
+                Some(&mut (ref mut parents, depth)) => (parents.pop(), depth),
+                None => return None,
+            };
+            let inherits = match inherits {
                 Some(inherits) => inherits,
-                None => continue,
+                None => {
+                    self.stack.pop();
+                    continue;
+                }
             };
 
             // We've walked this theme, avoid rebuilding.
-            if walked_themes.contains(inherits) {
+            if !self.walked_themes.insert(inherits.clone()) {
                 continue;
             }
 
-            let inherited_theme = CursorThemeIml::load(inherits, search_paths);
-
-            match inherited_theme.load_icon_with_depth(icon_name, search_paths, walked_themes) {
-                Some((icon_path, depth)) => return Some((icon_path, depth + 1)),
-                None => continue,
-            }
+            let theme = CursorThemeIml::load(&inherits, self.search_paths);
+            self.stack.push((theme.parents_reversed(), depth + 1));
+            return Some((theme, depth));
         }
-
-        None
     }
 }
 
@@ -159,6 +532,17 @@ impl SearchPathsEnvironment {
     }
 }
 
+/// Get the list of paths where the themes are searched by default, computed
+/// from the `HOME`, `XCURSOR_PATH`, `XDG_DATA_HOME` and `XDG_DATA_DIRS`
+/// environment variables.
+///
+/// If `XCURSOR_PATH` is set, it overrides all the other paths. Otherwise,
+/// the order follows the XDG Icon Theme specification and other XCursor
+/// loading libraries, like libwayland-cursor.
+pub fn default_search_paths() -> Vec<PathBuf> {
+    theme_search_paths(SearchPathsEnvironment::get())
+}
+
 /// Get the list of paths where the themes have to be searched, according to the XDG Icon Theme
 /// specification. If `XCURSOR_PATH` is set, it will override the default search paths.
 fn theme_search_paths(environment: SearchPathsEnvironment) -> Vec<PathBuf> {
@@ -237,18 +621,22 @@ fn expand_home_dir(path: PathBuf, home_dir: Option<&Path>) -> Option<PathBuf> {
     Some(path)
 }
 
-/// Load the specified index.theme file, and returns a `Some` with
-/// the value of the `Inherits` key in it.
-/// Returns `None` if the file cannot be read for any reason,
+/// Load the specified index.theme file, and returns the names
+/// of the themes listed in its `Inherits` key.
+/// Returns an empty `Vec` if the file cannot be read for any reason,
 /// if the file cannot be parsed, or if the `Inherits` key is omitted.
-fn theme_inherits(file_path: &Path) -> Option<String> {
-    let content = std::fs::read_to_string(file_path).ok()?;
-
-    parse_theme(&content)
+///
+/// Like libXcursor, and unlike [`ThemeIndex`](theme_index/struct.ThemeIndex.html), this
+/// ignores sections, so that `Inherits` keys outside of `[Icon Theme]` are honored.
+fn theme_inherits(file_path: &Path) -> Vec<String> {
+    match std::fs::read_to_string(file_path) {
+        Ok(content) => parse_theme(&content),
+        Err(_) => Vec::new(),
+    }
 }
 
-/// Parse the content of the `index.theme` and return the `Inherits` value.
-fn parse_theme(content: &str) -> Option<String> {
+/// Parse the content of the `index.theme` and return the names listed in the `Inherits` value.
+fn parse_theme(content: &str) -> Vec<String> {
     const PATTERN: &str = "Inherits";
 
     let is_xcursor_space_or_separator =
@@ -268,23 +656,27 @@ fn parse_theme(content: &str) -> Option<String> {
             continue;
         }
 
-        // Skip XCursor spaces/separators.
-        let result: String = chars
-            .skip_while(is_xcursor_space_or_separator)
-            .take_while(|ch| !is_xcursor_space_or_separator(ch))
+        // Split on XCursor spaces/separators.
+        let result: Vec<String> = chars
+            .as_str()
+            .split(|ch| is_xcursor_space_or_separator(&ch))
+            .filter(|name| !name.is_empty())
+            .map(String::from)
             .collect();
 
         if !result.is_empty() {
-            return Some(result);
+            return result;
         }
     }
 
-    None
+    Vec::new()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_util::{image, write_cursor, TempDir};
+    use std::fs;
     use std::path::{Path, PathBuf};
 
     #[test]
@@ -293,33 +685,207 @@ mod tests {
 
         let theme = format!("Inherits={}", theme_name.clone());
 
-        assert_eq!(parse_theme(&theme), Some(theme_name.clone()));
+        assert_eq!(parse_theme(&theme), vec![theme_name.clone()]);
 
         let theme = format!(" Inherits={}", theme_name.clone());
 
-        assert_eq!(parse_theme(&theme), None);
+        assert_eq!(parse_theme(&theme), Vec::<String>::new());
 
         let theme = format!(
             "[THEME name]\nInherits   = ,;\t\t{};;;;Tail\n\n",
             theme_name.clone()
         );
 
-        assert_eq!(parse_theme(&theme), Some(theme_name.clone()));
+        assert_eq!(
+            parse_theme(&theme),
+            vec![theme_name.clone(), String::from("Tail")]
+        );
 
         let theme = format!("Inherits;=;{}", theme_name.clone());
 
-        assert_eq!(parse_theme(&theme), None);
+        assert_eq!(parse_theme(&theme), Vec::<String>::new());
 
         let theme = format!("Inherits = {}\n\nInherits=OtherTheme", theme_name.clone());
 
-        assert_eq!(parse_theme(&theme), Some(theme_name.clone()));
+        assert_eq!(parse_theme(&theme), vec![theme_name.clone()]);
 
         let theme = format!(
             "Inherits = ;;\nSome\tgarbage\nInherits={}",
             theme_name.clone()
         );
 
-        assert_eq!(parse_theme(&theme), Some(theme_name.clone()));
+        assert_eq!(parse_theme(&theme), vec![theme_name.clone()]);
+    }
+
+    #[test]
+    fn test_multiple_inheritance() {
+        let dir = TempDir::new("multiple-inheritance");
+        let child = dir.add_theme("child", Some("Inherits=a, b"), &[]);
+        dir.add_theme("a", None, &["x"]);
+        dir.add_theme("b", Some("Inherits=c"), &["x", "y"]);
+        dir.add_theme("c", None, &["z"]);
+        dir.add_theme("default", None, &["w", "z"]);
+        let bare = dir.add_theme("bare", None, &[]);
+        fs::write(bare.join("index.theme"), "Inherits=a").unwrap();
+
+        let theme = CursorTheme::load_with_search_paths("child", vec![dir.0.clone()]);
+        assert_eq!(
+            theme.theme.data,
+            vec![(child, vec![String::from("a"), String::from("b")], false)]
+        );
+
+        // Like libXcursor, `Inherits` is read regardless of sections.
+        assert_eq!(
+            CursorThemeIml::load("bare", theme.search_paths()).data,
+            vec![(bare, vec![String::from("a")], false)]
+        );
+        let bare = CursorTheme::load_with_search_paths("bare", vec![dir.0.clone()]);
+        assert_eq!(bare.load_icon("x"), Some(dir.0.join("a/cursors/x")));
+
+        // Inherited themes are only loaded once the walk reaches them.
+        let mut chain = theme.theme.chain(theme.search_paths());
+        assert_eq!(chain.next().unwrap().0.name, "child");
+        assert_eq!(chain.walked_themes.len(), 1);
+        let rest: Vec<(String, usize)> = chain.map(|(theme, depth)| (theme.name, depth)).collect();
+        assert_eq!(
+            rest,
+            vec![
+                (String::from("a"), 1),
+                (String::from("default"), 2),
+                (String::from("b"), 1),
+                (String::from("c"), 2),
+            ]
+        );
+
+        let load = |name| theme.load_icon_with_depth(name);
+        assert_eq!(load("x"), Some((dir.0.join("a/cursors/x"), 1)));
+        assert_eq!(load("y"), Some((dir.0.join("b/cursors/y"), 1)));
+        // `a` implicitly inherits `default`, which is searched before `b`.
+        assert_eq!(load("z"), Some((dir.0.join("default/cursors/z"), 2)));
+        assert_eq!(load("w"), Some((dir.0.join("default/cursors/w"), 2)));
+        assert_eq!(load("v"), None);
+    }
+
+    #[test]
+    fn test_cursors() {
+        let dir = TempDir::new("cursors");
+        dir.add_theme("child", Some("Inherits=parent, child"), &["x"]);
+        dir.add_theme("parent", Some("Inherits=child"), &["y", "x"]);
+        dir.add_theme("default", None, &["z"]);
+
+        let theme = CursorTheme::load_with_search_paths("child", vec![dir.0.clone()]);
+        let cursors: Vec<ThemeCursor> = theme.cursors().collect();
+        assert_eq!(
+            cursors,
+            vec![
+                ThemeCursor {
+                    name: String::from("x"),
+                    theme: String::from("child"),
+                    path: dir.0.join("child/cursors/x"),
+                    depth: 0,
+                },
+                ThemeCursor {
+                    name: String::from("y"),
+                    theme: String::from("parent"),
+                    path: dir.0.join("parent/cursors/y"),
+                    depth: 1,
+                },
+            ]
+        );
+
+        let theme = CursorTheme::load_with_search_paths("missing", vec![dir.0.clone()]);
+        let names: Vec<String> = theme.cursors().map(|cursor| cursor.name).collect();
+        assert_eq!(names, Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_load_cursor() {
+        let dir = TempDir::new("load-cursor");
+        let child = dir.add_theme("child", Some("Inherits=parent"), &["x", "y"]);
+        let parent = dir.add_theme("parent", Some("Inherits=none"), &[]);
+        fs::write(child.join("cursors/x"), b"garbage").unwrap();
+        write_cursor(&child.join("cursors/y"), &[]);
+        let frames = vec![image(32, 10), image(32, 20)];
+        write_cursor(
+            &parent.join("cursors/x"),
+            &[image(24, 0), frames[0].clone(), frames[1].clone()],
+        );
+
+        let theme = CursorTheme::load_with_search_paths("child", vec![dir.0.clone()]);
+        assert_eq!(theme.load_cursor("x", 30).unwrap(), frames);
+
+        match theme.load_cursor("y", 24) {
+            Err(LoadError::NoImages { ref path }) if *path == child.join("cursors/y") => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+        match theme.load_cursor("z", 24) {
+            Err(LoadError::NotFound { ref name }) if name == "z" => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        fs::remove_file(parent.join("cursors/x")).unwrap();
+        match theme.load_cursor("x", 24) {
+            Err(LoadError::Parse {
+                error: ParseError::BadMagic { .. },
+                ..
+            }) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_aliases() {
+        let dir = TempDir::new("aliases");
+        let child = dir.add_theme("child", Some("Inherits=parent"), &["hand2"]);
+        let parent = dir.add_theme("parent", Some("Inherits=none"), &["pointer", "xterm"]);
+        write_cursor(&parent.join("cursors/xterm"), &[image(24, 0)]);
+
+        let theme = CursorTheme::load_with_search_paths("child", vec![dir.0.clone()]);
+        assert_eq!(
+            theme.load_icon_with_aliases("pointer"),
+            Some(child.join("cursors/hand2"))
+        );
+        assert_eq!(
+            theme.load_icon("pointer"),
+            Some(parent.join("cursors/pointer"))
+        );
+        assert_eq!(
+            theme.load_icon_with_aliases("hand2"),
+            Some(child.join("cursors/hand2"))
+        );
+        assert_eq!(theme.load_icon_with_aliases("wait"), None);
+
+        assert_eq!(
+            theme.load_cursor_with_aliases("text", 24).unwrap(),
+            vec![image(24, 0)]
+        );
+    }
+
+    #[test]
+    fn test_custom_search_paths() {
+        let dir = TempDir::new("custom-search-paths");
+        dir.add_theme("fixture", None, &["left_ptr"]);
+
+        let theme = CursorTheme::load_with_search_paths("fixture", vec![dir.0.clone()]);
+        assert_eq!(theme.search_paths(), &[dir.0.clone()][..]);
+        assert_eq!(
+            theme.load_icon("left_ptr"),
+            Some(dir.0.join("fixture/cursors/left_ptr"))
+        );
+
+        let theme = CursorTheme::builder("fixture")
+            .search_paths(vec![PathBuf::from("/b")])
+            .prepend_search_path(&dir.0)
+            .append_search_path("/c")
+            .build();
+        assert_eq!(
+            theme.search_paths(),
+            &[dir.0.clone(), PathBuf::from("/b"), PathBuf::from("/c")][..]
+        );
+        assert!(theme.load_icon("left_ptr").is_some());
+
+        let theme = CursorTheme::load_with_search_paths("fixture", vec![]);
+        assert_eq!(theme.load_icon("left_ptr"), None);
     }
 
     #[test]
@@ -0,0 +1,357 @@
+use crate::animation::AnimatedCursor;
+use crate::names;
+use crate::parser::{parse_xcursor_report, Image, ParseError, ParseOptions, Strictness};
+use crate::CursorTheme;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
+
+/// A problem found in a theme by [`lint_theme`](fn.lint_theme.html).
+#[derive(Debug)]
+pub enum LintIssue {
+    /// The theme has no `index.theme` file.
+    MissingIndex,
+    /// The theme has no `cursors` directory.
+    MissingCursorsDirectory,
+    /// A standard cursor, or any of its aliases, cannot be found in the
+    /// theme or in the themes it inherits from.
+    MissingCursor {
+        /// The CSS name of the cursor.
+        name: &'static str,
+    },
+    /// A symbolic link in the `cursors` directory points to a file that
+    /// doesn't exist.
+    DanglingSymlink { path: PathBuf },
+    /// A cursor file cannot be parsed.
+    Unparseable { path: PathBuf, error: ParseError },
+    /// A cursor file doesn't have the nominal sizes most cursors of the
+    /// theme have.
+    InconsistentSizes {
+        path: PathBuf,
+        /// The nominal sizes of the file.
+        sizes: Vec<u32>,
+        /// The nominal sizes of most cursors.
+        expected: Vec<u32>,
+    },
+    /// The hotspot of an image lies on its last column or row, which usually
+    /// means it was scaled or cropped wrong.
+    HotspotAtEdge {
+        path: PathBuf,
+        size: u32,
+        xhot: u32,
+        yhot: u32,
+    },
+    /// A frame of an animated cursor has a delay of zero, so it is never
+    /// shown.
+    ZeroDelayFrame {
+        path: PathBuf,
+        size: u32,
+        /// The index of the frame among the frames of its size.
+        frame: usize,
+    },
+}
+
+impl fmt::Display for LintIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            LintIssue::MissingIndex => write!(f, "Missing index.theme"),
+            LintIssue::MissingCursorsDirectory => write!(f, "Missing cursors directory"),
+            LintIssue::MissingCursor { name } => write!(f, "Missing cursor {:?}", name),
+            LintIssue::DanglingSymlink { ref path } => {
+                write!(f, "{}: Dangling symbolic link", path.display())
+            }
+            LintIssue::Unparseable {
+                ref path,
+                ref error,
+            } => write!(f, "{}: {}", path.display(), error),
+            LintIssue::InconsistentSizes {
+                ref path,
+                ref sizes,
+                ref expected,
+            } => write!(
+                f,
+                "{}: Nominal sizes {:?}, while most cursors have {:?}",
+                path.display(),
+                sizes,
+                expected
+            ),
+            LintIssue::HotspotAtEdge {
+                ref path,
+                size,
+                xhot,
+                yhot,
+            } => write!(
+                f,
+                "{}: Hotspot ({}, {}) of size {} lies on the image edge",
+                path.display(),
+                xhot,
+                yhot,
+                size
+            ),
+            LintIssue::ZeroDelayFrame {
+                ref path,
+                size,
+                frame,
+            } => write!(
+                f,
+                "{}: Frame {} of size {} has a delay of zero",
+                path.display(),
+                frame,
+                size
+            ),
+        }
+    }
+}
+
+/// Check a theme for problems, given its directory, like
+/// `/usr/share/icons/Adwaita`.
+///
+/// Only `theme_dir` is checked, even if a theme with the same name is
+/// installed in `search_paths`. Inherited themes are searched next to the
+/// theme, then in `search_paths`; they are only used to check for missing
+/// cursors. Symbolic links in the
+/// `cursors` directory are only checked for pointing to an existing file,
+/// while the other files are parsed in
+/// [`Strictness::Strict`](../parser/enum.Strictness.html) mode, so that
+/// inconsistencies libXcursor rejects are reported as unparseable files.
+///
+/// Returns an error if `theme_dir` cannot be read. It is canonicalized
+/// first, so relative paths like `.` are accepted, and the paths of the
+/// issues are absolute.
+pub fn lint_theme(theme_dir: &Path, search_paths: &[PathBuf]) -> io::Result<Vec<LintIssue>> {
+    let theme_dir = &fs::canonicalize(theme_dir)?;
+    let name = theme_dir
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Bad theme directory"))?;
+    fs::read_dir(theme_dir)?;
+
+    let mut issues = Vec::new();
+    if !theme_dir.join("index.theme").is_file() {
+        issues.push(LintIssue::MissingIndex);
+    }
+
+    let cursors_dir = theme_dir.join("cursors");
+    match fs::read_dir(&cursors_dir) {
+        Ok(entries) => {
+            let mut paths: Vec<PathBuf> = entries
+                .filter_map(Result::ok)
+                .map(|entry| entry.path())
+                .collect();
+            paths.sort();
+            lint_cursors(&paths, &mut issues);
+        }
+        Err(_) => issues.push(LintIssue::MissingCursorsDirectory),
+    }
+
+    let mut theme_search_paths = search_paths.to_vec();
+    if let Some(parent) = theme_dir.parent() {
+        theme_search_paths.insert(0, parent.to_owned());
+    }
+    let theme = CursorTheme::load_from_directory(name, theme_dir, theme_search_paths);
+    for name in names::css_names() {
+        if theme.load_icon_with_aliases(name).is_none() {
+            issues.push(LintIssue::MissingCursor { name });
+        }
+    }
+
+    Ok(issues)
+}
+
+/// Check the files of a `cursors` directory.
+fn lint_cursors(paths: &[PathBuf], issues: &mut Vec<LintIssue>) {
+    let mut sizes: Vec<(PathBuf, Vec<u32>)> = Vec::new();
+
+    for path in paths {
+        let is_symlink = fs::symlink_metadata(path)
+            .map(|metadata| metadata.file_type().is_symlink())
+            .unwrap_or(false);
+        if is_symlink {
+            if fs::metadata(path).is_err() {
+                issues.push(LintIssue::DanglingSymlink { path: path.clone() });
+            }
+            continue;
+        }
+        if !path.is_file() {
+            continue;
+        }
+
+        let options = ParseOptions {
+            strictness: Strictness::Strict,
+            ..Default::default()
+        };
+        let result = File::open(path)
+            .map_err(|error| ParseError::Io {
+                offset: 0,
+                toc_index: None,
+                error,
+            })
+            .and_then(|file| parse_xcursor_report(&mut BufReader::new(file), &options));
+        let images = match result {
+            Ok(report) => report.file.images,
+            Err(error) => {
+                issues.push(LintIssue::Unparseable {
+                    path: path.clone(),
+                    error,
+                });
+                continue;
+            }
+        };
+
+        sizes.push((path.clone(), nominal_sizes(&images)));
+        lint_images(path, images, issues);
+    }
+
+    // The sizes shared by the most files are taken as the expected ones.
+    let expected = sizes
+        .iter()
+        .map(|(_, file_sizes)| file_sizes)
+        .max_by_key(|file_sizes| {
+            sizes
+                .iter()
+                .filter(|&(_, other)| other == *file_sizes)
+                .count()
+        })
+        .cloned();
+    if let Some(expected) = expected {
+        for (path, file_sizes) in sizes {
+            if file_sizes != expected {
+                issues.push(LintIssue::InconsistentSizes {
+                    path,
+                    sizes: file_sizes,
+                    expected: expected.clone(),
+                });
+            }
+        }
+    }
+}
+
+/// Check the images of a cursor file.
+fn lint_images(path: &Path, images: Vec<Image>, issues: &mut Vec<LintIssue>) {
+    for cursor in AnimatedCursor::group(images) {
+        let frames = cursor.frames();
+
+        if let Some(image) = frames
+            .iter()
+            .find(|image| image.xhot + 1 >= image.width || image.yhot + 1 >= image.height)
+        {
+            issues.push(LintIssue::HotspotAtEdge {
+                path: path.to_owned(),
+                size: cursor.size(),
+                xhot: image.xhot,
+                yhot: image.yhot,
+            });
+        }
+
+        if frames.len() > 1 {
+            for (frame, image) in frames.iter().enumerate() {
+                if image.delay == 0 {
+                    issues.push(LintIssue::ZeroDelayFrame {
+                        path: path.to_owned(),
+                        size: cursor.size(),
+                        frame,
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// The distinct nominal sizes of some images, sorted.
+fn nominal_sizes(images: &[Image]) -> Vec<u32> {
+    let mut sizes: Vec<u32> = images.iter().map(|image| image.size).collect();
+    sizes.sort();
+    sizes.dedup();
+    sizes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{lint_theme, LintIssue};
+    use crate::parser::{Image, ParseError};
+    use crate::test_util::{image, write_cursor, TempDir};
+    use std::fs;
+
+    #[test]
+    fn test_lint_theme() {
+        let dir = TempDir::new("lint");
+        let theme = dir.add_theme("theme", None, &[]);
+        let cursors = fs::canonicalize(&theme).unwrap().join("cursors");
+        write_cursor(&cursors.join("left_ptr"), &[image(24, 0), image(32, 0)]);
+        let edge = Image {
+            xhot: 1,
+            yhot: 1,
+            ..image(24, 0)
+        };
+        write_cursor(&cursors.join("xterm"), &[edge]);
+        write_cursor(
+            &cursors.join("watch"),
+            &[image(24, 10), image(32, 0), image(24, 0)],
+        );
+        fs::write(cursors.join("hand2"), b"garbage").unwrap();
+        // Patch the subtype of the only TOC entry, which follows the 16-byte
+        // file header and the 4-byte entry type.
+        write_cursor(&cursors.join("hand1"), &[image(24, 0)]);
+        let mut content = fs::read(cursors.join("hand1")).unwrap();
+        content[20..24].copy_from_slice(&32u32.to_le_bytes());
+        fs::write(cursors.join("hand1"), content).unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink("missing", cursors.join("dangling")).unwrap();
+
+        let issues = lint_theme(&theme, &[]).unwrap();
+        let messages: Vec<String> = issues.iter().map(ToString::to_string).collect();
+        let message =
+            |path: &str, text: &str| format!("{}: {}", cursors.join(path).display(), text);
+
+        assert_eq!(messages[0], "Missing index.theme");
+        #[cfg(unix)]
+        assert!(messages.contains(&message("dangling", "Dangling symbolic link")));
+        assert!(issues.iter().any(|issue| match *issue {
+            LintIssue::Unparseable { ref path, .. } => *path == cursors.join("hand2"),
+            _ => false,
+        }));
+        assert!(issues.iter().any(|issue| match *issue {
+            LintIssue::Unparseable {
+                ref path,
+                error: ParseError::StrictViolation(_),
+            } => *path == cursors.join("hand1"),
+            _ => false,
+        }));
+        assert!(messages
+            .iter()
+            .any(|text| text.contains("Chunk subtype 24 doesn't match TOC subtype 32")));
+        assert!(messages.contains(&message(
+            "xterm",
+            "Nominal sizes [24], while most cursors have [24, 32]"
+        )));
+        assert!(messages.contains(&message(
+            "xterm",
+            "Hotspot (1, 1) of size 24 lies on the image edge"
+        )));
+        assert!(messages.contains(&message("watch", "Frame 1 of size 24 has a delay of zero")));
+        assert!(!messages.iter().any(|text| text.contains("left_ptr")));
+
+        assert!(messages.contains(&String::from("Missing cursor \"crosshair\"")));
+        assert!(!messages.contains(&String::from("Missing cursor \"default\"")));
+        assert!(!messages.contains(&String::from("Missing cursor \"text\"")));
+        assert!(!messages.contains(&String::from("Missing cursor \"wait\"")));
+
+        let relative = lint_theme(&cursors.join(".."), &[]).unwrap();
+        assert_eq!(relative.len(), issues.len());
+
+        // An installed copy of the theme doesn't hide its missing cursors.
+        let installed = TempDir::new("lint-installed");
+        installed.add_theme("theme", Some("Inherits=parent"), &["crosshair"]);
+        installed.add_theme("parent", None, &["cell"]);
+        let messages: Vec<String> = lint_theme(&theme, std::slice::from_ref(&installed.0))
+            .unwrap()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert!(messages.contains(&String::from("Missing cursor \"crosshair\"")));
+        assert!(messages.contains(&String::from("Missing cursor \"cell\"")));
+
+        assert!(lint_theme(&dir.0.join("missing"), &[]).is_err());
+    }
+}
@@ -0,0 +1,317 @@
+/// The names of the cursors, in the order they are tried, for each CSS
+/// cursor name.
+///
+/// The first name of each list is the CSS name itself, which modern themes
+/// use. It is followed by the legacy X cursor font names, the names used by
+/// other toolkits, and the hashes of the bitmaps of the legacy cursors that
+/// some applications look up.
+const ALIASES: &[(&str, &[&str])] = &[
+    (
+        "default",
+        &[
+            "default",
+            "left_ptr",
+            "arrow",
+            "top_left_arrow",
+            "left_arrow",
+        ],
+    ),
+    ("context-menu", &["context-menu"]),
+    (
+        "help",
+        &[
+            "help",
+            "question_arrow",
+            "whats_this",
+            "left_ptr_help",
+            "5c6cd98b3f3ebcb1f9c7f1c204630408",
+            "d9ce0ab605698f320427677b458ad60b",
+        ],
+    ),
+    (
+        "pointer",
+        &[
+            "pointer",
+            "hand2",
+            "hand",
+            "hand1",
+            "pointing_hand",
+            "e29285e634086352946a0e7090d73106",
+            "9d800788f1b08800ae810202380a0822",
+        ],
+    ),
+    (
+        "progress",
+        &[
+            "progress",
+            "left_ptr_watch",
+            "half-busy",
+            "08e8e1c95fe2fc01f976f1e063a24ccd",
+            "3ecb610c1bf2410f44200f48c40d3599",
+        ],
+    ),
+    ("wait", &["wait", "watch", "clock"]),
+    ("cell", &["cell", "plus"]),
+    (
+        "crosshair",
+        &[
+            "crosshair",
+            "cross",
+            "tcross",
+            "cross_reverse",
+            "diamond_cross",
+        ],
+    ),
+    ("text", &["text", "xterm", "ibeam"]),
+    (
+        "vertical-text",
+        &["vertical-text", "048008013003cff3c00c801001200000"],
+    ),
+    (
+        "alias",
+        &[
+            "alias",
+            "link",
+            "dnd-link",
+            "0876e1c15ff2fc01f906f1c363074c0f",
+            "3085a0e285430894940527032f8b26df",
+            "640fb0e74195791501fd1ed57b41487f",
+            "a2a266d0498c3104214a47bd64ab0fc8",
+        ],
+    ),
+    (
+        "copy",
+        &[
+            "copy",
+            "dnd-copy",
+            "1081e37283d90000800003c07f3ef6bf",
+            "6407b0e94181790501fd1e167b474872",
+            "b66166c04f8c3109214a4fbd64a50fc8",
+        ],
+    ),
+    (
+        "move",
+        &[
+            "move",
+            "dnd-move",
+            "fleur",
+            "4498f0e0c1937ffe01fd06f973665830",
+            "9081237383d90e509aa00f00170e968f",
+        ],
+    ),
+    (
+        "no-drop",
+        &["no-drop", "dnd-none", "dnd-no-drop", "forbidden", "circle"],
+    ),
+    (
+        "not-allowed",
+        &[
+            "not-allowed",
+            "crossed_circle",
+            "forbidden",
+            "circle",
+            "03b6e0fcb3499374a867c041f52298f0",
+        ],
+    ),
+    (
+        "grab",
+        &[
+            "grab",
+            "openhand",
+            "hand1",
+            "5aca4d189052212118709018842178c0",
+            "9141b49c8149039304290b508d208c40",
+        ],
+    ),
+    (
+        "grabbing",
+        &[
+            "grabbing",
+            "closedhand",
+            "fleur",
+            "208530c400c041818281048008011002",
+        ],
+    ),
+    ("e-resize", &["e-resize", "right_side"]),
+    ("n-resize", &["n-resize", "top_side"]),
+    ("ne-resize", &["ne-resize", "top_right_corner"]),
+    ("nw-resize", &["nw-resize", "top_left_corner"]),
+    ("s-resize", &["s-resize", "bottom_side"]),
+    ("se-resize", &["se-resize", "bottom_right_corner"]),
+    ("sw-resize", &["sw-resize", "bottom_left_corner"]),
+    ("w-resize", &["w-resize", "left_side"]),
+    (
+        "ew-resize",
+        &[
+            "ew-resize",
+            "sb_h_double_arrow",
+            "h_double_arrow",
+            "size_hor",
+            "028006030e0e7ebffc7f7070c0600140",
+        ],
+    ),
+    (
+        "ns-resize",
+        &[
+            "ns-resize",
+            "sb_v_double_arrow",
+            "v_double_arrow",
+            "size_ver",
+            "00008160000006810000408080010102",
+        ],
+    ),
+    (
+        "nesw-resize",
+        &[
+            "nesw-resize",
+            "fd_double_arrow",
+            "size_bdiag",
+            "fcf1c3c7cd4491d801f1e1c78f100000",
+        ],
+    ),
+    (
+        "nwse-resize",
+        &[
+            "nwse-resize",
+            "bd_double_arrow",
+            "size_fdiag",
+            "c7088f0f3e6c8088236ef8e1e3e70000",
+        ],
+    ),
+    (
+        "col-resize",
+        &[
+            "col-resize",
+            "split_h",
+            "sb_h_double_arrow",
+            "14fef782d02440884392942c11205230",
+        ],
+    ),
+    (
+        "row-resize",
+        &[
+            "row-resize",
+            "split_v",
+            "sb_v_double_arrow",
+            "2870a09082c103050810ffdffffe0204",
+        ],
+    ),
+    ("all-scroll", &["all-scroll", "fleur", "size_all"]),
+    ("zoom-in", &["zoom-in", "f41c0e382c94c0958e07017e42b00462"]),
+    (
+        "zoom-out",
+        &["zoom-out", "f41c0e382c97c0938e07017e42800402"],
+    ),
+    ("dnd-ask", &["dnd-ask", "dnd-copy", "copy"]),
+    ("all-resize", &["all-resize", "fleur", "size_all"]),
+];
+
+/// The CSS names of the `wp_cursor_shape_device_v1` shapes, indexed by the
+/// value of the shape minus one.
+const WAYLAND_SHAPES: &[&str] = &[
+    "default",
+    "context-menu",
+    "help",
+    "pointer",
+    "progress",
+    "wait",
+    "cell",
+    "crosshair",
+    "text",
+    "vertical-text",
+    "alias",
+    "copy",
+    "move",
+    "no-drop",
+    "not-allowed",
+    "grab",
+    "grabbing",
+    "e-resize",
+    "n-resize",
+    "ne-resize",
+    "nw-resize",
+    "s-resize",
+    "se-resize",
+    "sw-resize",
+    "w-resize",
+    "ew-resize",
+    "ns-resize",
+    "nesw-resize",
+    "nwse-resize",
+    "col-resize",
+    "row-resize",
+    "all-scroll",
+    "zoom-in",
+    "zoom-out",
+    "dnd-ask",
+    "all-resize",
+];
+
+/// Get the file names to try, in order, for a CSS cursor name like
+/// `pointer`. The list starts with the name itself.
+///
+/// Returns `None` for names that aren't CSS cursor names, including the
+/// legacy X names like `left_ptr`.
+pub fn aliases(name: &str) -> Option<&'static [&'static str]> {
+    ALIASES
+        .iter()
+        .find(|&&(css_name, _)| css_name == name)
+        .map(|&(_, aliases)| aliases)
+}
+
+/// The CSS cursor names, which every theme should provide under one of
+/// their [`aliases`](fn.aliases.html).
+pub fn css_names() -> impl Iterator<Item = &'static str> {
+    ALIASES.iter().map(|&(name, _)| name)
+}
+
+/// Get the CSS cursor name of a `wp_cursor_shape_device_v1` shape, from the
+/// value of the `shape` enum of the protocol, like `"pointer"` for 4.
+///
+/// Returns `None` for unknown shapes.
+pub fn wayland_shape_name(shape: u32) -> Option<&'static str> {
+    if shape == 0 {
+        return None;
+    }
+
+    WAYLAND_SHAPES.get(shape as usize - 1).cloned()
+}
+
+/// The names to try for a cursor: its aliases if it is a CSS name, or
+/// itself.
+pub(crate) fn lookup_names(name: &str) -> Vec<&str> {
+    match aliases(name) {
+        Some(aliases) => aliases.to_vec(),
+        None => vec![name],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{aliases, lookup_names, wayland_shape_name, ALIASES, WAYLAND_SHAPES};
+
+    #[test]
+    fn test_aliases() {
+        assert_eq!(aliases("text"), Some(&["text", "xterm", "ibeam"][..]));
+        assert_eq!(aliases("left_ptr"), None);
+        assert_eq!(lookup_names("left_ptr"), vec!["left_ptr"]);
+        assert_eq!(lookup_names("pointer")[..2], ["pointer", "hand2"]);
+
+        for &(name, aliases) in ALIASES {
+            assert_eq!(aliases[0], name);
+        }
+        for &shape in WAYLAND_SHAPES {
+            assert!(aliases(shape).is_some(), "{} has no aliases", shape);
+        }
+    }
+
+    #[test]
+    fn test_wayland_shape_name() {
+        assert_eq!(wayland_shape_name(0), None);
+        assert_eq!(wayland_shape_name(1), Some("default"));
+        assert_eq!(wayland_shape_name(4), Some("pointer"));
+        assert_eq!(wayland_shape_name(34), Some("zoom-out"));
+        assert_eq!(wayland_shape_name(36), Some("all-resize"));
+        assert_eq!(wayland_shape_name(37), None);
+    }
+}
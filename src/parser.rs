@@ -1,17 +1,41 @@
 use std::{
     fmt::{self, Debug, Formatter},
-    io::{Cursor, Error, ErrorKind, Read, Result as IoResult, Seek, SeekFrom},
+    io::{Cursor, Error, ErrorKind, Read, Result as IoResult, Seek, SeekFrom, Write},
 };
 
+/// An entry of the table of contents of an XCursor file.
 #[derive(Debug, Clone, Eq, PartialEq)]
-struct Toc {
-    toctype: u32,
-    subtype: u32,
-    pos: u32,
+pub struct Toc {
+    /// The type of the chunk this entry points to.
+    pub toctype: u32,
+
+    /// The subtype of the chunk: the nominal size for images, and the
+    /// kind of comment for comments.
+    pub subtype: u32,
+
+    /// The byte offset of the chunk in the file.
+    pub pos: u32,
+}
+
+impl Toc {
+    /// Whether this entry points to an image chunk.
+    pub fn is_image(&self) -> bool {
+        self.toctype == IMAGE_TYPE
+    }
+
+    /// Whether this entry points to a comment chunk.
+    pub fn is_comment(&self) -> bool {
+        self.toctype == COMMENT_TYPE
+    }
 }
 
 /// A struct representing an image.
-/// Pixels are in ARGB format, with each byte representing a single channel.
+///
+/// XCursor files store each pixel as a little-endian 32-bit ARGB value with
+/// premultiplied alpha, so the bytes of a pixel are B, G, R, A. Use
+/// [`pixels_argb32`](#method.pixels_argb32) or
+/// [`convert_pixels`](#method.convert_pixels) to get them in another format;
+/// only the bytes of the file are kept in memory.
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub struct Image {
     /// The nominal size of the image.
@@ -32,11 +56,51 @@ pub struct Image {
     /// The amount of time (in milliseconds) that this image should be shown for, before switching to the next.
     pub delay: u32,
 
-    /// A slice containing the pixels' bytes, in RGBA format (or, in the order of the file).
-    pub pixels_rgba: Vec<u8>,
+    /// The pixels' bytes, in the order of the file: B, G, R, A, with
+    /// premultiplied alpha.
+    pub pixels: Vec<u8>,
+}
+
+/// The order of the channels of a pixel, one byte per channel.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum ChannelOrder {
+    /// Blue, green, red, alpha. This is how XCursor files store pixels, and
+    /// the byte order of a little-endian `0xAARRGGBB` value (e.g. Wayland's
+    /// `ARGB8888`).
+    Bgra,
+
+    /// Red, green, blue, alpha.
+    Rgba,
+
+    /// Alpha, red, green, blue.
+    Argb,
+
+    /// Alpha, blue, green, red.
+    Abgr,
+}
+
+/// How the color channels of a pixel relate to its alpha channel.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum AlphaMode {
+    /// The color channels are multiplied by the alpha value, as in XCursor
+    /// files.
+    Premultiplied,
 
-    /// A slice containing the pixels' bytes, in ARGB format.
-    pub pixels_argb: Vec<u8>,
+    /// The color channels are independent of the alpha value.
+    Straight,
+}
+
+impl Image {
+    /// The pixels as native-endian `0xAARRGGBB` values with premultiplied
+    /// alpha, as they are defined by the XCursor format.
+    pub fn pixels_argb32(&self) -> Vec<u32> {
+        bgra_to_argb32(&self.pixels)
+    }
+
+    /// The pixels' bytes in the given channel order and alpha mode.
+    pub fn convert_pixels(&self, order: ChannelOrder, alpha: AlphaMode) -> Vec<u8> {
+        convert_bgra(&self.pixels, order, alpha)
+    }
 }
 
 impl std::fmt::Display for Image {
@@ -53,19 +117,538 @@ impl std::fmt::Display for Image {
     }
 }
 
-fn parse_header(i: &mut impl Read) -> IoResult<(u32, u32)> {
-    i.tag(*b"Xcur")?;
-    let header = i.u32_le()?;
-    let _version = i.u32_le()?;
-    let ntoc = i.u32_le()?;
+/// An image whose pixels are borrowed from the buffer it was parsed from,
+/// such as a memory-mapped XCursor file.
+///
+/// The fields have the same meaning as the ones of [`Image`].
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub struct ImageRef<'a> {
+    /// The nominal size of the image.
+    pub size: u32,
+
+    /// The actual width of the image. Doesn't need to match `size`.
+    pub width: u32,
+
+    /// The actual height of the image. Doesn't need to match `size`.
+    pub height: u32,
+
+    /// The X coordinate of the hotspot pixel (the pixel where the tip of the arrow is situated)
+    pub xhot: u32,
+
+    /// The Y coordinate of the hotspot pixel (the pixel where the tip of the arrow is situated)
+    pub yhot: u32,
+
+    /// The amount of time (in milliseconds) that this image should be shown for, before switching to the next.
+    pub delay: u32,
+
+    /// The pixels' bytes, in the order of the file: B, G, R, A, with
+    /// premultiplied alpha.
+    pub pixels: &'a [u8],
+}
+
+impl<'a> ImageRef<'a> {
+    /// Copy the image into an owned [`Image`].
+    pub fn to_image(&self) -> Image {
+        Image {
+            size: self.size,
+            width: self.width,
+            height: self.height,
+            xhot: self.xhot,
+            yhot: self.yhot,
+            delay: self.delay,
+            pixels: self.pixels.to_vec(),
+        }
+    }
+
+    /// The pixels as native-endian `0xAARRGGBB` values with premultiplied
+    /// alpha, as they are defined by the XCursor format.
+    pub fn pixels_argb32(&self) -> Vec<u32> {
+        bgra_to_argb32(self.pixels)
+    }
+
+    /// The pixels' bytes in the given channel order and alpha mode.
+    pub fn convert_pixels(&self, order: ChannelOrder, alpha: AlphaMode) -> Vec<u8> {
+        convert_bgra(self.pixels, order, alpha)
+    }
+}
+
+/// The kind of a comment chunk.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum CommentKind {
+    /// A copyright notice.
+    Copyright,
+
+    /// The license the cursor is distributed under.
+    License,
+
+    /// Any other text.
+    Other,
+
+    /// A comment subtype not defined by libXcursor.
+    Unknown(u32),
+}
+
+impl CommentKind {
+    /// Get the kind of comment corresponding to a chunk subtype.
+    pub fn from_subtype(subtype: u32) -> CommentKind {
+        match subtype {
+            1 => CommentKind::Copyright,
+            2 => CommentKind::License,
+            3 => CommentKind::Other,
+            subtype => CommentKind::Unknown(subtype),
+        }
+    }
+
+    /// The chunk subtype used to store this kind of comment.
+    pub fn subtype(self) -> u32 {
+        match self {
+            CommentKind::Copyright => 1,
+            CommentKind::License => 2,
+            CommentKind::Other => 3,
+            CommentKind::Unknown(subtype) => subtype,
+        }
+    }
+}
+
+/// A text comment stored in an XCursor file.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Comment {
+    /// What the comment is about.
+    pub kind: CommentKind,
+
+    /// The text of the comment.
+    pub text: String,
+}
+
+/// An error encountered while parsing an XCursor file.
+///
+/// Apart from `BadMagic`, every variant carries the byte offset in the file
+/// where the problem was found, and the index of the TOC entry that was being
+/// processed (when the error happened inside a chunk).
+#[derive(Debug)]
+pub enum ParseError {
+    /// The file doesn't start with the `Xcur` magic bytes.
+    BadMagic {
+        /// The first four bytes of the file.
+        found: [u8; 4],
+    },
+
+    /// A field of a chunk header doesn't have the expected value.
+    BadChunkHeader {
+        offset: u64,
+        toc_index: u32,
+        expected: u32,
+        found: u32,
+    },
+
+    /// The image is wider or taller than 0x7fff pixels.
+    ImageTooLarge {
+        offset: u64,
+        toc_index: u32,
+        width: u32,
+        height: u32,
+    },
+
+    /// The image has a width or height of zero.
+    ZeroSize {
+        offset: u64,
+        toc_index: u32,
+        width: u32,
+        height: u32,
+    },
+
+    /// The hotspot lies outside of the image.
+    HotspotOutsideImage {
+        offset: u64,
+        toc_index: u32,
+        xhot: u32,
+        yhot: u32,
+        width: u32,
+        height: u32,
+    },
+
+    /// The file ends before all of the image's pixels could be read.
+    TruncatedPixelData {
+        offset: u64,
+        toc_index: u32,
+        /// The number of bytes of pixel data the image header declares.
+        expected: usize,
+    },
+
+    /// A comment is longer than the 0x100000 bytes allowed by libXcursor.
+    CommentTooLong {
+        offset: u64,
+        toc_index: u32,
+        /// The length declared in the comment header.
+        length: u32,
+    },
+
+    /// The file ends before the whole text of a comment could be read.
+    TruncatedComment {
+        offset: u64,
+        toc_index: u32,
+        /// The length declared in the comment header.
+        expected: usize,
+    },
+
+    /// A TOC entry points past the end of the file.
+    TocOffsetOutOfBounds {
+        offset: u64,
+        toc_index: u32,
+        /// The position stored in the TOC entry.
+        position: u32,
+        /// The length of the file.
+        file_len: u64,
+    },
+
+    /// The file exceeds one of the limits set in [`ParseOptions`].
+    LimitExceeded {
+        offset: u64,
+        toc_index: Option<u32>,
+        /// The limit that was exceeded.
+        limit: Limit,
+        /// The value that exceeded the limit.
+        value: u64,
+    },
+
+    /// An inconsistency that is only an error in strict mode.
+    StrictViolation(Warning),
+
+    /// An I/O error, including an unexpected end of file.
+    Io {
+        offset: u64,
+        toc_index: Option<u32>,
+        error: Error,
+    },
+}
+
+impl ParseError {
+    /// The byte offset in the file where the error happened.
+    pub fn offset(&self) -> u64 {
+        match *self {
+            ParseError::BadMagic { .. } => 0,
+            ParseError::BadChunkHeader { offset, .. }
+            | ParseError::ImageTooLarge { offset, .. }
+            | ParseError::ZeroSize { offset, .. }
+            | ParseError::HotspotOutsideImage { offset, .. }
+            | ParseError::TruncatedPixelData { offset, .. }
+            | ParseError::CommentTooLong { offset, .. }
+            | ParseError::TruncatedComment { offset, .. }
+            | ParseError::TocOffsetOutOfBounds { offset, .. }
+            | ParseError::LimitExceeded { offset, .. }
+            | ParseError::Io { offset, .. } => offset,
+            ParseError::StrictViolation(ref warning) => warning.offset(),
+        }
+    }
+
+    /// The index of the TOC entry that was being processed, if any.
+    pub fn toc_index(&self) -> Option<u32> {
+        match *self {
+            ParseError::BadMagic { .. } => None,
+            ParseError::BadChunkHeader { toc_index, .. }
+            | ParseError::ImageTooLarge { toc_index, .. }
+            | ParseError::ZeroSize { toc_index, .. }
+            | ParseError::HotspotOutsideImage { toc_index, .. }
+            | ParseError::TruncatedPixelData { toc_index, .. }
+            | ParseError::CommentTooLong { toc_index, .. }
+            | ParseError::TruncatedComment { toc_index, .. }
+            | ParseError::TocOffsetOutOfBounds { toc_index, .. } => Some(toc_index),
+            ParseError::LimitExceeded { toc_index, .. } | ParseError::Io { toc_index, .. } => {
+                toc_index
+            }
+            ParseError::StrictViolation(ref warning) => Some(warning.toc_index()),
+        }
+    }
+}
+
+/// An inconsistency between a chunk and its TOC entry, or an unknown chunk
+/// header field, that doesn't prevent decoding the chunk.
+///
+/// Warnings are collected by [`XcursorReader`], and turned into
+/// [`ParseError::StrictViolation`] in [`Strictness::Strict`] mode.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Warning {
+    /// The chunk header is longer than the fields this crate knows about.
+    /// The extra fields were skipped.
+    LongChunkHeader {
+        offset: u64,
+        toc_index: u32,
+        /// The header length stored in the chunk.
+        header_len: u32,
+    },
+
+    /// The chunk has a version this crate doesn't know about.
+    UnknownVersion {
+        offset: u64,
+        toc_index: u32,
+        version: u32,
+    },
+
+    /// The subtype of the chunk (the nominal size for images) doesn't match
+    /// the one in its TOC entry. The chunk's subtype is used.
+    SubtypeMismatch {
+        offset: u64,
+        toc_index: u32,
+        toc_subtype: u32,
+        chunk_subtype: u32,
+    },
+}
+
+impl Warning {
+    /// The byte offset in the file of the offending field.
+    pub fn offset(&self) -> u64 {
+        match *self {
+            Warning::LongChunkHeader { offset, .. }
+            | Warning::UnknownVersion { offset, .. }
+            | Warning::SubtypeMismatch { offset, .. } => offset,
+        }
+    }
+
+    /// The index of the TOC entry pointing to the chunk.
+    pub fn toc_index(&self) -> u32 {
+        match *self {
+            Warning::LongChunkHeader { toc_index, .. }
+            | Warning::UnknownVersion { toc_index, .. }
+            | Warning::SubtypeMismatch { toc_index, .. } => toc_index,
+        }
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match *self {
+            Warning::LongChunkHeader { header_len, .. } => {
+                write!(f, "Chunk header too long ({} bytes)", header_len)
+            }
+            Warning::UnknownVersion { version, .. } => {
+                write!(f, "Unknown chunk version {}", version)
+            }
+            Warning::SubtypeMismatch {
+                toc_subtype,
+                chunk_subtype,
+                ..
+            } => write!(
+                f,
+                "Chunk subtype {} doesn't match TOC subtype {}",
+                chunk_subtype, toc_subtype
+            ),
+        }?;
+
+        write!(
+            f,
+            " in TOC entry {} at offset {:#x}",
+            self.toc_index(),
+            self.offset()
+        )
+    }
+}
+
+/// How to handle inconsistencies reported as [`Warning`]s, and chunks that
+/// can't be decoded.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Strictness {
+    /// Record warnings and skip the chunks that can't be decoded, so that
+    /// the rest of the file can still be used. Errors in the file header and
+    /// table of contents, I/O errors and exceeded limits are still fatal.
+    Lenient,
+
+    /// Record warnings, and fail on chunks that can't be decoded.
+    Normal,
+
+    /// Fail on warnings with [`ParseError::StrictViolation`], and on chunks
+    /// that can't be decoded. This rejects every chunk libXcursor would
+    /// reject.
+    Strict,
+}
+
+/// A resource limit enforced while parsing, see [`ParseOptions`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Limit {
+    /// The number of entries in the table of contents.
+    TocEntries,
+
+    /// The number of images in the table of contents.
+    Images,
+
+    /// The total number of bytes of pixel data decoded by a reader.
+    PixelBytes,
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match *self {
+            Limit::TocEntries => "TOC entries",
+            Limit::Images => "images",
+            Limit::PixelBytes => "bytes of pixel data",
+        })
+    }
+}
+
+/// Options controlling how XCursor files are parsed.
+///
+/// The limits protect against hostile files, e.g. ones claiming billions of
+/// TOC entries, and are enforced before allocating memory. The defaults are
+/// generous enough for any real cursor.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ParseOptions {
+    /// The maximum number of entries in the table of contents. Defaults to
+    /// 0x10000, like libXcursor.
+    pub max_toc_entries: u32,
+
+    /// The maximum number of images in the table of contents. Defaults to
+    /// 4096.
+    pub max_images: u32,
+
+    /// The maximum number of bytes of pixel data a single reader will
+    /// decode, summed over all images. Defaults to 256 MiB.
+    pub max_pixel_bytes: u64,
+
+    /// How to handle inconsistencies in chunk headers. Defaults to
+    /// `Strictness::Normal`.
+    pub strictness: Strictness,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            max_toc_entries: 0x10000,
+            max_images: 4096,
+            max_pixel_bytes: 256 * 1024 * 1024,
+            strictness: Strictness::Normal,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match *self {
+            ParseError::BadMagic { found } => {
+                write!(f, "Not an XCursor file (magic bytes {:?})", found)
+            }
+            ParseError::BadChunkHeader {
+                expected, found, ..
+            } => write!(
+                f,
+                "Bad chunk header field (expected {:#x}, found {:#x})",
+                expected, found
+            ),
+            ParseError::ImageTooLarge { width, height, .. } => {
+                write!(f, "Image too large ({}x{})", width, height)
+            }
+            ParseError::ZeroSize { width, height, .. } => {
+                write!(f, "Image with zero width or height ({}x{})", width, height)
+            }
+            ParseError::HotspotOutsideImage {
+                xhot,
+                yhot,
+                width,
+                height,
+                ..
+            } => write!(
+                f,
+                "Hotspot ({}, {}) outside image ({}x{})",
+                xhot, yhot, width, height
+            ),
+            ParseError::TruncatedPixelData { expected, .. } => {
+                write!(f, "Truncated pixel data (expected {} bytes)", expected)
+            }
+            ParseError::CommentTooLong { length, .. } => {
+                write!(f, "Comment too long ({} bytes)", length)
+            }
+            ParseError::TruncatedComment { expected, .. } => {
+                write!(f, "Truncated comment (expected {} bytes)", expected)
+            }
+            ParseError::TocOffsetOutOfBounds {
+                position, file_len, ..
+            } => write!(
+                f,
+                "TOC entry points to {:#x}, past the end of the file ({} bytes)",
+                position, file_len
+            ),
+            ParseError::LimitExceeded { limit, value, .. } => {
+                write!(f, "Limit exceeded: {} {}", value, limit)
+            }
+            ParseError::StrictViolation(ref warning) => return fmt::Display::fmt(warning, f),
+            ParseError::Io { ref error, .. } => write!(f, "I/O error: {}", error),
+        }?;
+
+        if let Some(toc_index) = self.toc_index() {
+            write!(f, " in TOC entry {}", toc_index)?;
+        }
+        write!(f, " at offset {:#x}", self.offset())
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            ParseError::Io { ref error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(err: ParseError) -> Error {
+        match err {
+            ParseError::Io { error, .. } => error,
+            err => Error::new(ErrorKind::InvalidData, err),
+        }
+    }
+}
+
+/// The type of TOC entries and chunks containing an image.
+const IMAGE_TYPE: u32 = 0xfffd_0002;
+
+/// The length of an image chunk header.
+const IMAGE_HEADER_LEN: u32 = 36;
+
+/// The version of image chunks.
+const IMAGE_VERSION: u32 = 1;
+
+/// The type of TOC entries and chunks containing a comment.
+const COMMENT_TYPE: u32 = 0xfffe_0001;
+
+/// The length of a comment chunk header.
+const COMMENT_HEADER_LEN: u32 = 20;
+
+/// The version of comment chunks.
+const COMMENT_VERSION: u32 = 1;
+
+/// The maximum length of a comment, in bytes.
+const COMMENT_MAX_LEN: usize = 0x10_0000;
+
+/// The length of the file header.
+const FILE_HEADER_LEN: u32 = 16;
+
+/// The version of the file format.
+const FILE_VERSION: u32 = 0x0001_0000;
+
+/// The length of a TOC entry.
+const TOC_ENTRY_LEN: u64 = 12;
+
+fn parse_header(i: &mut impl Read) -> Result<(u32, u32), ParseError> {
+    let mut magic = [0u8; 4];
+    i.read_exact(&mut magic).map_err(|error| ParseError::Io {
+        offset: 0,
+        toc_index: None,
+        error,
+    })?;
+    if &magic != b"Xcur" {
+        return Err(ParseError::BadMagic { found: magic });
+    }
+    let header = field(i, 4, None)?;
+    let _version = field(i, 8, None)?;
+    let ntoc = field(i, 12, None)?;
 
     Ok((header, ntoc))
 }
 
-fn parse_toc(i: &mut impl Read) -> IoResult<Toc> {
-    let toctype = i.u32_le()?; // Type
-    let subtype = i.u32_le()?; // Subtype
-    let pos = i.u32_le()?; // Position
+/// Parse the TOC entry with the given index, located at `offset`.
+fn parse_toc(i: &mut impl Read, offset: u64, index: u32) -> Result<Toc, ParseError> {
+    let toctype = field(i, offset, Some(index))?; // Type
+    let subtype = field(i, offset + 4, Some(index))?; // Subtype
+    let pos = field(i, offset + 8, Some(index))?; // Position
 
     Ok(Toc {
         toctype,
@@ -74,96 +657,892 @@ fn parse_toc(i: &mut impl Read) -> IoResult<Toc> {
     })
 }
 
-fn parse_img(i: &mut impl Read) -> IoResult<Image> {
-    i.tag([0x24, 0x00, 0x00, 0x00])?; // Header size
-    i.tag([0x02, 0x00, 0xfd, 0xff])?; // Type
-    let size = i.u32_le()?;
-    i.tag([0x01, 0x00, 0x00, 0x00])?; // Image version (1)
-    let width = i.u32_le()?;
-    let height = i.u32_le()?;
-    let xhot = i.u32_le()?;
-    let yhot = i.u32_le()?;
-    let delay = i.u32_le()?;
+/// The fields of an image chunk header.
+struct ImageHeader {
+    size: u32,
+    width: u32,
+    height: u32,
+    xhot: u32,
+    yhot: u32,
+    delay: u32,
+    /// The byte offset of the pixel data in the file.
+    pixels_offset: u64,
+    /// The length of the pixel data, in bytes.
+    pixels_len: usize,
+}
+
+/// Context for validating a chunk against the TOC entry pointing to it.
+struct ChunkContext<'a> {
+    toc: &'a Toc,
+    toc_index: u32,
+    strictness: Strictness,
+    warnings: &'a mut Vec<Warning>,
+}
+
+impl<'a> ChunkContext<'a> {
+    /// Record a warning, or fail in strict mode.
+    fn warn(&mut self, warning: Warning) -> Result<(), ParseError> {
+        match self.strictness {
+            Strictness::Lenient | Strictness::Normal => {
+                self.warnings.push(warning);
+                Ok(())
+            }
+            Strictness::Strict => Err(ParseError::StrictViolation(warning)),
+        }
+    }
+}
+
+/// The generic fields at the start of every chunk.
+struct ChunkHeader {
+    /// The length of the whole chunk header.
+    header_len: u32,
+    subtype: u32,
+}
+
+/// Parse the generic fields of the chunk located at `pos`, checking them
+/// against its TOC entry and the given chunk type, header length and version.
+fn parse_chunk_header(
+    i: &mut impl Read,
+    pos: u64,
+    ctx: &mut ChunkContext<'_>,
+    chunk_type: u32,
+    min_header_len: u32,
+    version: u32,
+) -> Result<ChunkHeader, ParseError> {
+    let toc_index = ctx.toc_index;
+
+    let header_len = field(i, pos, Some(toc_index))?; // Header size
+    if header_len < min_header_len {
+        return Err(ParseError::BadChunkHeader {
+            offset: pos,
+            toc_index,
+            expected: min_header_len,
+            found: header_len,
+        });
+    }
+    expect_field(i, pos + 4, toc_index, chunk_type)?; // Type
+    let subtype = field(i, pos + 8, Some(toc_index))?;
+    let found_version = field(i, pos + 12, Some(toc_index))?;
+
+    if header_len > min_header_len {
+        ctx.warn(Warning::LongChunkHeader {
+            offset: pos,
+            toc_index,
+            header_len,
+        })?;
+    }
+    if subtype != ctx.toc.subtype {
+        ctx.warn(Warning::SubtypeMismatch {
+            offset: pos + 8,
+            toc_index,
+            toc_subtype: ctx.toc.subtype,
+            chunk_subtype: subtype,
+        })?;
+    }
+    if found_version != version {
+        ctx.warn(Warning::UnknownVersion {
+            offset: pos + 12,
+            toc_index,
+            version: found_version,
+        })?;
+    }
+
+    Ok(ChunkHeader {
+        header_len,
+        subtype,
+    })
+}
+
+/// Skip the fields of a chunk header this crate doesn't know about, located
+/// between `pos + known_len` and the end of the header.
+fn skip_header_fields(
+    i: &mut impl Seek,
+    pos: u64,
+    header: &ChunkHeader,
+    known_len: u32,
+    toc_index: u32,
+) -> Result<(), ParseError> {
+    if header.header_len > known_len {
+        seek(i, pos + u64::from(header.header_len), Some(toc_index))?;
+    }
+    Ok(())
+}
+
+/// Parse the header of the image chunk located at `pos`, leaving the stream
+/// at the start of the pixel data.
+fn parse_img_header<I: Read + Seek>(
+    i: &mut I,
+    pos: u64,
+    ctx: &mut ChunkContext<'_>,
+) -> Result<ImageHeader, ParseError> {
+    let toc_index = ctx.toc_index;
+    let chunk = parse_chunk_header(i, pos, ctx, IMAGE_TYPE, IMAGE_HEADER_LEN, IMAGE_VERSION)?;
+    let size = chunk.subtype;
+    let width = field(i, pos + 16, Some(toc_index))?;
+    let height = field(i, pos + 20, Some(toc_index))?;
+    let xhot = field(i, pos + 24, Some(toc_index))?;
+    let yhot = field(i, pos + 28, Some(toc_index))?;
+    let delay = field(i, pos + 32, Some(toc_index))?;
+    skip_header_fields(i, pos, &chunk, IMAGE_HEADER_LEN, toc_index)?;
 
     // Check image is well-formed. Taken from https://gitlab.freedesktop.org/xorg/lib/libxcursor/-/blob/09617bcc9a0f1b5072212da5f8fede92ab85d157/src/file.c#L456-463
     if width > 0x7fff || height > 0x7fff {
-        return Err(Error::new(ErrorKind::Other, "Image too large"));
+        return Err(ParseError::ImageTooLarge {
+            offset: pos,
+            toc_index,
+            width,
+            height,
+        });
     }
     if width == 0 || height == 0 {
-        return Err(Error::new(
-            ErrorKind::Other,
-            "Image with zero width or height",
-        ));
+        return Err(ParseError::ZeroSize {
+            offset: pos,
+            toc_index,
+            width,
+            height,
+        });
     }
     if xhot > width || yhot > height {
-        return Err(Error::new(ErrorKind::Other, "Hotspot outside image"));
+        return Err(ParseError::HotspotOutsideImage {
+            offset: pos,
+            toc_index,
+            xhot,
+            yhot,
+            width,
+            height,
+        });
     }
 
-    let img_length: usize = (4 * width * height) as usize;
-    let pixels_rgba = i.take_bytes(img_length)?;
-    let pixels_argb = rgba_to_argb(&pixels_rgba);
-
-    Ok(Image {
+    Ok(ImageHeader {
         size,
         width,
         height,
         xhot,
         yhot,
         delay,
-        pixels_argb,
-        pixels_rgba,
+        pixels_offset: pos + u64::from(chunk.header_len),
+        pixels_len: (4 * width * height) as usize,
     })
 }
 
-/// Converts a RGBA slice into an ARGB vec
+/// Read the pixels following the given image chunk header, referenced by the
+/// TOC entry `toc_index`.
+fn parse_img(i: &mut impl Read, header: ImageHeader, toc_index: u32) -> Result<Image, ParseError> {
+    let pixels = i.take_bytes(header.pixels_len).map_err(|error| {
+        if error.kind() == ErrorKind::UnexpectedEof {
+            ParseError::TruncatedPixelData {
+                offset: header.pixels_offset,
+                toc_index,
+                expected: header.pixels_len,
+            }
+        } else {
+            ParseError::Io {
+                offset: header.pixels_offset,
+                toc_index: Some(toc_index),
+                error,
+            }
+        }
+    })?;
+
+    Ok(Image {
+        size: header.size,
+        width: header.width,
+        height: header.height,
+        xhot: header.xhot,
+        yhot: header.yhot,
+        delay: header.delay,
+        pixels,
+    })
+}
+
+/// Parse the comment chunk located at `pos`.
+fn parse_comment<I: Read + Seek>(
+    i: &mut I,
+    pos: u64,
+    ctx: &mut ChunkContext<'_>,
+) -> Result<Comment, ParseError> {
+    let toc_index = ctx.toc_index;
+    let chunk = parse_chunk_header(
+        i,
+        pos,
+        ctx,
+        COMMENT_TYPE,
+        COMMENT_HEADER_LEN,
+        COMMENT_VERSION,
+    )?;
+    let subtype = chunk.subtype;
+    let length = field(i, pos + 16, Some(toc_index))?;
+    skip_header_fields(i, pos, &chunk, COMMENT_HEADER_LEN, toc_index)?;
+
+    if length as usize > COMMENT_MAX_LEN {
+        return Err(ParseError::CommentTooLong {
+            offset: pos,
+            toc_index,
+            length,
+        });
+    }
+
+    let text_offset = pos + u64::from(chunk.header_len);
+    let text = i.take_bytes(length as usize).map_err(|error| {
+        if error.kind() == ErrorKind::UnexpectedEof {
+            ParseError::TruncatedComment {
+                offset: text_offset,
+                toc_index,
+                expected: length as usize,
+            }
+        } else {
+            ParseError::Io {
+                offset: text_offset,
+                toc_index: Some(toc_index),
+                error,
+            }
+        }
+    })?;
+
+    Ok(Comment {
+        kind: CommentKind::from_subtype(subtype),
+        text: String::from_utf8_lossy(&text).into_owned(),
+    })
+}
+
+/// Read a 32-bit little endian field located at `offset`.
+fn field(i: &mut impl Read, offset: u64, toc_index: Option<u32>) -> Result<u32, ParseError> {
+    i.u32_le().map_err(|error| ParseError::Io {
+        offset,
+        toc_index,
+        error,
+    })
+}
+
+/// Read a 32-bit little endian chunk header field located at `offset`,
+/// and check that it has the expected value.
+fn expect_field(
+    i: &mut impl Read,
+    offset: u64,
+    toc_index: u32,
+    expected: u32,
+) -> Result<(), ParseError> {
+    let found = field(i, offset, Some(toc_index))?;
+    if found != expected {
+        return Err(ParseError::BadChunkHeader {
+            offset,
+            toc_index,
+            expected,
+            found,
+        });
+    }
+    Ok(())
+}
+
+/// Converts a BGRA slice into native-endian `0xAARRGGBB` values.
 ///
 /// Note that, if the input length is not
 /// a multiple of 4, the extra elements are ignored.
-fn rgba_to_argb(i: &[u8]) -> Vec<u8> {
+fn bgra_to_argb32(i: &[u8]) -> Vec<u32> {
+    i.chunks_exact(4)
+        .map(|bgra| u32::from_le_bytes([bgra[0], bgra[1], bgra[2], bgra[3]]))
+        .collect()
+}
+
+/// Converts a BGRA slice with premultiplied alpha into the given channel
+/// order and alpha mode.
+///
+/// Note that, if the input length is not
+/// a multiple of 4, the extra elements are ignored.
+fn convert_bgra(i: &[u8], order: ChannelOrder, alpha: AlphaMode) -> Vec<u8> {
     let mut res = Vec::with_capacity(i.len());
 
-    for rgba in i.chunks_exact(4) {
-        res.push(rgba[3]);
-        res.push(rgba[0]);
-        res.push(rgba[1]);
-        res.push(rgba[2]);
+    for bgra in i.chunks_exact(4) {
+        let (mut b, mut g, mut r, a) = (bgra[0], bgra[1], bgra[2], bgra[3]);
+        if alpha == AlphaMode::Straight {
+            b = unpremultiply(b, a);
+            g = unpremultiply(g, a);
+            r = unpremultiply(r, a);
+        }
+
+        res.extend_from_slice(&match order {
+            ChannelOrder::Bgra => [b, g, r, a],
+            ChannelOrder::Rgba => [r, g, b, a],
+            ChannelOrder::Argb => [a, r, g, b],
+            ChannelOrder::Abgr => [a, b, g, r],
+        });
     }
 
     res
 }
 
+/// Divide a premultiplied color channel by its alpha value, rounding to
+/// the nearest integer.
+fn unpremultiply(channel: u8, alpha: u8) -> u8 {
+    if alpha == 0 {
+        return 0;
+    }
+
+    let straight = (u32::from(channel) * 255 + u32::from(alpha) / 2) / u32::from(alpha);
+    straight.min(255) as u8
+}
+
 /// Parse an XCursor file into its images.
+///
+/// Returns `None` if the file is malformed; use [`parse_xcursor_stream`] to
+/// find out why.
 pub fn parse_xcursor(content: &[u8]) -> Option<Vec<Image>> {
     parse_xcursor_stream(&mut Cursor::new(content)).ok()
 }
 
 /// Parse an XCursor file into its images.
-pub fn parse_xcursor_stream<R: Read + Seek>(input: &mut R) -> IoResult<Vec<Image>> {
-    let (header, ntoc) = parse_header(input)?;
-    input.seek(SeekFrom::Start(header as u64))?;
+pub fn parse_xcursor_stream<R: Read + Seek>(input: &mut R) -> Result<Vec<Image>, ParseError> {
+    parse_stream(input, ParseOptions::default(), false).map(|report| report.file.images)
+}
 
-    let mut img_indices = Vec::new();
-    for _ in 0..ntoc {
-        let toc = parse_toc(input)?;
+/// Parse the images of an XCursor file that best match the requested size,
+/// picking them the same way as libXcursor. See [`XcursorReader::load_images`].
+pub fn parse_xcursor_sized(content: &[u8], size: u32) -> Result<Vec<Image>, ParseError> {
+    XcursorReader::new(Cursor::new(content))?.load_images(size)
+}
+
+/// The contents of an XCursor file.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct XcursorFile {
+    /// The images, in the order of the file's table of contents.
+    pub images: Vec<Image>,
 
-        if toc.toctype == 0xfffd_0002 {
-            img_indices.push(toc.pos);
+    /// The comments, in the order of the file's table of contents.
+    pub comments: Vec<Comment>,
+}
+
+/// Parse an XCursor file into its images, without copying their pixels.
+///
+/// This is meant for files that are already in memory, e.g. because they
+/// have been memory-mapped.
+pub fn parse_xcursor_ref(content: &[u8]) -> Result<Vec<ImageRef<'_>>, ParseError> {
+    let mut reader = XcursorReader::new(Cursor::new(content))?;
+
+    let mut images = Vec::new();
+    for index in 0..reader.toc().len() as u32 {
+        if reader.toc()[index as usize].is_image() {
+            images.push(reader.read_image_ref(index)?);
         }
     }
+    Ok(images)
+}
+
+/// Parse an XCursor file into its images and comments.
+pub fn parse_xcursor_file(content: &[u8]) -> Result<XcursorFile, ParseError> {
+    parse_xcursor_file_stream(&mut Cursor::new(content))
+}
+
+/// Parse an XCursor file into its images and comments.
+///
+/// To parse with other [`ParseOptions`], use [`parse_xcursor_report`] and
+/// take its `file`.
+pub fn parse_xcursor_file_stream<R: Read + Seek>(input: &mut R) -> Result<XcursorFile, ParseError> {
+    parse_stream(input, ParseOptions::default(), true).map(|report| report.file)
+}
+
+/// The result of parsing an XCursor file, along with the problems found
+/// while doing so.
+#[derive(Debug)]
+pub struct ParseReport {
+    /// The images and comments that could be decoded.
+    pub file: XcursorFile,
+
+    /// The inconsistencies that didn't prevent decoding a chunk.
+    pub warnings: Vec<Warning>,
+
+    /// The errors of the chunks that were skipped, in
+    /// [`Strictness::Lenient`] mode.
+    pub skipped: Vec<ParseError>,
+}
+
+/// Parse an XCursor file into its images and comments, with the given
+/// options, reporting every problem found.
+///
+/// In [`Strictness::Lenient`] mode this returns the surviving chunks when
+/// some of them are malformed, and is suitable for displaying cursors; in
+/// [`Strictness::Strict`] mode it is suitable for linting them.
+pub fn parse_xcursor_report<R: Read + Seek>(
+    input: &mut R,
+    options: &ParseOptions,
+) -> Result<ParseReport, ParseError> {
+    parse_stream(input, *options, true)
+}
+
+/// The implementation of XCursor parsing. Comment chunks are only
+/// parsed if `with_comments` is set.
+fn parse_stream<R: Read + Seek>(
+    input: &mut R,
+    options: ParseOptions,
+    with_comments: bool,
+) -> Result<ParseReport, ParseError> {
+    let mut reader = XcursorReader::with_options(input, options)?;
 
-    let mut imgs = Vec::with_capacity(ntoc as usize);
-    for index in img_indices {
-        input.seek(SeekFrom::Start(index.into()))?;
-        imgs.push(parse_img(input)?);
+    let mut file = XcursorFile::default();
+    for index in 0..reader.toc().len() as u32 {
+        let toc = &reader.toc()[index as usize];
+        if toc.is_image() {
+            let result = reader.read_image(index);
+            file.images.extend(reader.recover(result)?);
+        } else if with_comments && toc.is_comment() {
+            let result = reader.read_comment(index);
+            file.comments.extend(reader.recover(result)?);
+        }
     }
 
-    Ok(imgs)
+    Ok(ParseReport {
+        file,
+        warnings: reader.warnings,
+        skipped: reader.skipped,
+    })
+}
+
+/// A reader that parses the header and table of contents of an XCursor
+/// file up front, and decodes images and comments only when asked to.
+///
+/// This avoids allocating pixel data for images that are never used, e.g.
+/// all the nominal sizes but the one that will be displayed.
+#[derive(Debug)]
+pub struct XcursorReader<R> {
+    input: R,
+    options: ParseOptions,
+    file_len: u64,
+    /// The byte offset of the table of contents.
+    toc_offset: u64,
+    toc: Vec<Toc>,
+    /// The number of bytes of pixel data decoded so far.
+    pixel_bytes: u64,
+    warnings: Vec<Warning>,
+    /// The errors of the chunks skipped in lenient mode.
+    skipped: Vec<ParseError>,
 }
 
-trait StreamExt {
-    /// Parse a series of bytes, returning `None` if it doesn't exist.
-    fn tag(&mut self, tag: [u8; 4]) -> IoResult<()>;
+impl<R: Read + Seek> XcursorReader<R> {
+    /// Read the header and table of contents of an XCursor file, using the
+    /// default [`ParseOptions`].
+    pub fn new(input: R) -> Result<Self, ParseError> {
+        XcursorReader::with_options(input, ParseOptions::default())
+    }
+
+    /// Read the header and table of contents of an XCursor file.
+    pub fn with_options(mut input: R, options: ParseOptions) -> Result<Self, ParseError> {
+        let file_len = input
+            .seek(SeekFrom::End(0))
+            .map_err(|error| ParseError::Io {
+                offset: 0,
+                toc_index: None,
+                error,
+            })?;
+        seek(&mut input, 0, None)?;
+
+        let (header, ntoc) = parse_header(&mut input)?;
+        if ntoc > options.max_toc_entries {
+            return Err(ParseError::LimitExceeded {
+                offset: 12,
+                toc_index: None,
+                limit: Limit::TocEntries,
+                value: ntoc.into(),
+            });
+        }
+
+        let toc_offset = u64::from(header);
+        seek(&mut input, toc_offset, None)?;
+
+        let mut toc = Vec::new();
+        for index in 0..ntoc {
+            let offset = toc_offset + u64::from(index) * TOC_ENTRY_LEN;
+            toc.push(parse_toc(&mut input, offset, index)?);
+        }
 
+        let images = toc.iter().filter(|toc| toc.is_image()).count() as u64;
+        if images > options.max_images.into() {
+            return Err(ParseError::LimitExceeded {
+                offset: toc_offset,
+                toc_index: None,
+                limit: Limit::Images,
+                value: images,
+            });
+        }
+
+        Ok(XcursorReader {
+            input,
+            options,
+            file_len,
+            toc_offset,
+            toc,
+            pixel_bytes: 0,
+            warnings: Vec::new(),
+            skipped: Vec::new(),
+        })
+    }
+
+    /// The entries of the file's table of contents. They can be passed
+    /// by index to `read_image` and `read_comment`.
+    pub fn toc(&self) -> &[Toc] {
+        &self.toc
+    }
+
+    /// The distinct nominal sizes of the images in the file, in the order
+    /// of the table of contents.
+    pub fn nominal_sizes(&self) -> Vec<u32> {
+        let mut sizes = Vec::new();
+        for toc in self.toc.iter().filter(|toc| toc.is_image()) {
+            if !sizes.contains(&toc.subtype) {
+                sizes.push(toc.subtype);
+            }
+        }
+        sizes
+    }
+
+    /// Find the nominal size that libXcursor would pick for the requested
+    /// size: the one closest to it, preferring the first one in the table of
+    /// contents in case of ties. Images with a nominal size of zero are
+    /// never picked.
+    ///
+    /// Returns `None` if the file contains no suitable image.
+    pub fn best_size(&self, size: u32) -> Option<u32> {
+        let dist = |a: u32, b: u32| if a > b { a - b } else { b - a };
+
+        // Mirrors `_XcursorFindBestSize`, where a size of zero means "none yet".
+        let mut best = 0;
+        for toc in self.toc.iter().filter(|toc| toc.is_image()) {
+            if best == 0 || dist(toc.subtype, size) < dist(best, size) {
+                best = toc.subtype;
+            }
+        }
+
+        if best == 0 {
+            None
+        } else {
+            Some(best)
+        }
+    }
+
+    /// Decode the images libXcursor's `XcursorFileLoadImages` would return
+    /// for the requested size: all the frames with the nominal size chosen
+    /// by `best_size`, in the order of the table of contents.
+    ///
+    /// Returns an empty `Vec` if the file contains no suitable image. In
+    /// lenient mode, frames that can't be decoded are skipped.
+    pub fn load_images(&mut self, size: u32) -> Result<Vec<Image>, ParseError> {
+        let indices = self.best_size_indices(size);
+
+        let mut images = Vec::with_capacity(indices.len());
+        for index in indices {
+            let result = self.read_image(index);
+            images.extend(self.recover(result)?);
+        }
+        Ok(images)
+    }
+
+    /// The indices of the TOC entries of the images with the nominal size
+    /// chosen by `best_size`.
+    fn best_size_indices(&self, size: u32) -> Vec<u32> {
+        let best = match self.best_size(size) {
+            Some(best) => best,
+            None => return Vec::new(),
+        };
+
+        (0..self.toc.len() as u32)
+            .filter(|&i| self.toc[i as usize].is_image() && self.toc[i as usize].subtype == best)
+            .collect()
+    }
+
+    /// Decode the image pointed to by the given TOC entry.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `toc_index` is out of bounds.
+    pub fn read_image(&mut self, toc_index: u32) -> Result<Image, ParseError> {
+        let header = self.read_img_header(toc_index)?;
+        parse_img(&mut self.input, header, toc_index)
+    }
+
+    /// Decode the comment pointed to by the given TOC entry.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `toc_index` is out of bounds.
+    pub fn read_comment(&mut self, toc_index: u32) -> Result<Comment, ParseError> {
+        let pos = self.seek_to_chunk(toc_index)?;
+        let mut ctx = ChunkContext {
+            toc: &self.toc[toc_index as usize],
+            toc_index,
+            strictness: self.options.strictness,
+            warnings: &mut self.warnings,
+        };
+        parse_comment(&mut self.input, pos, &mut ctx)
+    }
+
+    /// The warnings collected so far while decoding chunks.
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    /// The errors of the chunks skipped so far by `load_images` and
+    /// `load_image_refs` in lenient mode.
+    pub fn skipped(&self) -> &[ParseError] {
+        &self.skipped
+    }
+
+    /// In lenient mode, turn the error of a chunk that can't be decoded into
+    /// `None`, recording it.
+    fn recover<T>(&mut self, result: Result<T, ParseError>) -> Result<Option<T>, ParseError> {
+        match result {
+            Ok(value) => Ok(Some(value)),
+            Err(err) => {
+                let fatal = match err {
+                    ParseError::LimitExceeded { .. } => true,
+                    ParseError::Io { ref error, .. } => error.kind() != ErrorKind::UnexpectedEof,
+                    _ => false,
+                };
+                if self.options.strictness != Strictness::Lenient || fatal {
+                    return Err(err);
+                }
+
+                self.skipped.push(err);
+                Ok(None)
+            }
+        }
+    }
+
+    /// Get back the underlying stream.
+    pub fn into_inner(self) -> R {
+        self.input
+    }
+
+    /// Parse the header of the image pointed to by the given TOC entry, and
+    /// check that its pixels can be decoded.
+    fn read_img_header(&mut self, toc_index: u32) -> Result<ImageHeader, ParseError> {
+        let pos = self.seek_to_chunk(toc_index)?;
+        let mut ctx = ChunkContext {
+            toc: &self.toc[toc_index as usize],
+            toc_index,
+            strictness: self.options.strictness,
+            warnings: &mut self.warnings,
+        };
+        let header = parse_img_header(&mut self.input, pos, &mut ctx)?;
+        self.reserve_pixels(&header, toc_index)?;
+        Ok(header)
+    }
+
+    /// Check that the pixels of the given image are within the file and the
+    /// limits, before allocating memory for them.
+    fn reserve_pixels(&mut self, header: &ImageHeader, toc_index: u32) -> Result<(), ParseError> {
+        let len = header.pixels_len as u64;
+        if header.pixels_offset + len > self.file_len {
+            return Err(ParseError::TruncatedPixelData {
+                offset: header.pixels_offset,
+                toc_index,
+                expected: header.pixels_len,
+            });
+        }
+
+        let total = self.pixel_bytes + len;
+        if total > self.options.max_pixel_bytes {
+            return Err(ParseError::LimitExceeded {
+                offset: header.pixels_offset,
+                toc_index: Some(toc_index),
+                limit: Limit::PixelBytes,
+                value: total,
+            });
+        }
+
+        self.pixel_bytes = total;
+        Ok(())
+    }
+
+    /// Seek to the start of the chunk pointed to by the given TOC entry,
+    /// returning its position.
+    fn seek_to_chunk(&mut self, toc_index: u32) -> Result<u64, ParseError> {
+        let toc = &self.toc[toc_index as usize];
+        let pos = u64::from(toc.pos);
+        if pos >= self.file_len {
+            return Err(ParseError::TocOffsetOutOfBounds {
+                offset: self.toc_offset + u64::from(toc_index) * TOC_ENTRY_LEN,
+                toc_index,
+                position: toc.pos,
+                file_len: self.file_len,
+            });
+        }
+
+        seek(&mut self.input, pos, Some(toc_index))?;
+        Ok(pos)
+    }
+}
+
+impl<'a> XcursorReader<Cursor<&'a [u8]>> {
+    /// Decode the header of the image pointed to by the given TOC entry,
+    /// borrowing its pixels from the underlying buffer instead of copying
+    /// them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `toc_index` is out of bounds.
+    pub fn read_image_ref(&mut self, toc_index: u32) -> Result<ImageRef<'a>, ParseError> {
+        let header = self.read_img_header(toc_index)?;
+
+        let content: &'a [u8] = self.input.get_ref();
+        let start = header.pixels_offset as usize;
+        let pixels = start
+            .checked_add(header.pixels_len)
+            .and_then(|end| content.get(start..end))
+            .ok_or(ParseError::TruncatedPixelData {
+                offset: header.pixels_offset,
+                toc_index,
+                expected: header.pixels_len,
+            })?;
+
+        Ok(ImageRef {
+            size: header.size,
+            width: header.width,
+            height: header.height,
+            xhot: header.xhot,
+            yhot: header.yhot,
+            delay: header.delay,
+            pixels,
+        })
+    }
+
+    /// Like `load_images`, but borrowing the pixels from the underlying
+    /// buffer instead of copying them.
+    pub fn load_image_refs(&mut self, size: u32) -> Result<Vec<ImageRef<'a>>, ParseError> {
+        let indices = self.best_size_indices(size);
+
+        let mut images = Vec::with_capacity(indices.len());
+        for index in indices {
+            let result = self.read_image_ref(index);
+            images.extend(self.recover(result)?);
+        }
+        Ok(images)
+    }
+}
+
+/// Seek to the given position from the start of the stream.
+fn seek(input: &mut impl Seek, pos: u64, toc_index: Option<u32>) -> Result<(), ParseError> {
+    input
+        .seek(SeekFrom::Start(pos))
+        .map(|_| ())
+        .map_err(|error| ParseError::Io {
+            offset: pos,
+            toc_index,
+            error,
+        })
+}
+
+/// Write an XCursor file containing the given images and comments.
+///
+/// Images are stored in the given order, followed by the comments, like
+/// `xcursorgen` does. The pixels are taken from `Image::pixels`, which
+/// must contain exactly `4 * width * height` bytes.
+///
+/// Returns an error of kind `InvalidInput` if an image would be rejected by
+/// the parser, if a comment is longer than libXcursor allows, or if the file
+/// would exceed the limits of `ParseOptions::default()`, so that everything
+/// written can be read back with the default options.
+pub fn write_xcursor<W: Write>(
+    output: &mut W,
+    images: &[Image],
+    comments: &[Comment],
+) -> IoResult<()> {
+    let invalid = |msg: &str| Err(Error::new(ErrorKind::InvalidInput, msg));
+
+    for image in images {
+        if image.width > 0x7fff || image.height > 0x7fff {
+            return invalid("Image too large");
+        }
+        if image.width == 0 || image.height == 0 {
+            return invalid("Image with zero width or height");
+        }
+        if image.xhot > image.width || image.yhot > image.height {
+            return invalid("Hotspot outside image");
+        }
+        if image.pixels.len() != (4 * image.width * image.height) as usize {
+            return invalid("Pixel data doesn't match image dimensions");
+        }
+    }
+    if comments.iter().any(|c| c.text.len() > COMMENT_MAX_LEN) {
+        return invalid("Comment too long");
+    }
+
+    let limits = ParseOptions::default();
+    let ntoc = images.len() + comments.len();
+    if ntoc > limits.max_toc_entries as usize {
+        return invalid("Too many TOC entries");
+    }
+    if images.len() > limits.max_images as usize {
+        return invalid("Too many images");
+    }
+    let pixel_bytes: u64 = images.iter().map(|img| img.pixels.len() as u64).sum();
+    if pixel_bytes > limits.max_pixel_bytes {
+        return invalid("Too much pixel data");
+    }
+
+    let toc_len = ntoc as u64 * TOC_ENTRY_LEN;
+    let chunks_len: u64 = images
+        .iter()
+        .map(|img| u64::from(IMAGE_HEADER_LEN) + img.pixels.len() as u64)
+        .chain(
+            comments
+                .iter()
+                .map(|c| u64::from(COMMENT_HEADER_LEN) + c.text.len() as u64),
+        )
+        .sum();
+    if u64::from(FILE_HEADER_LEN) + toc_len + chunks_len > u64::from(u32::max_value()) {
+        return invalid("File too large");
+    }
+
+    // File header
+    output.write_all(b"Xcur")?;
+    write_u32_le(output, FILE_HEADER_LEN)?;
+    write_u32_le(output, FILE_VERSION)?;
+    write_u32_le(output, ntoc as u32)?;
+
+    // TOC
+    let mut pos = FILE_HEADER_LEN + toc_len as u32;
+    for image in images {
+        write_u32_le(output, IMAGE_TYPE)?;
+        write_u32_le(output, image.size)?;
+        write_u32_le(output, pos)?;
+        pos += IMAGE_HEADER_LEN + image.pixels.len() as u32;
+    }
+    for comment in comments {
+        write_u32_le(output, COMMENT_TYPE)?;
+        write_u32_le(output, comment.kind.subtype())?;
+        write_u32_le(output, pos)?;
+        pos += COMMENT_HEADER_LEN + comment.text.len() as u32;
+    }
+
+    // Chunks
+    for image in images {
+        write_u32_le(output, IMAGE_HEADER_LEN)?;
+        write_u32_le(output, IMAGE_TYPE)?;
+        write_u32_le(output, image.size)?;
+        write_u32_le(output, IMAGE_VERSION)?;
+        write_u32_le(output, image.width)?;
+        write_u32_le(output, image.height)?;
+        write_u32_le(output, image.xhot)?;
+        write_u32_le(output, image.yhot)?;
+        write_u32_le(output, image.delay)?;
+        output.write_all(&image.pixels)?;
+    }
+    for comment in comments {
+        write_u32_le(output, COMMENT_HEADER_LEN)?;
+        write_u32_le(output, COMMENT_TYPE)?;
+        write_u32_le(output, comment.kind.subtype())?;
+        write_u32_le(output, COMMENT_VERSION)?;
+        write_u32_le(output, comment.text.len() as u32)?;
+        output.write_all(comment.text.as_bytes())?;
+    }
+
+    Ok(())
+}
+
+/// Write a 32-bit little endian number.
+fn write_u32_le(output: &mut impl Write, n: u32) -> IoResult<()> {
+    output.write_all(&n.to_le_bytes())
+}
+
+trait StreamExt {
     /// Take a slice of bytes.
     fn take_bytes(&mut self, len: usize) -> IoResult<Vec<u8>>;
 
@@ -172,16 +1551,6 @@ trait StreamExt {
 }
 
 impl<R: Read> StreamExt for R {
-    fn tag(&mut self, tag: [u8; 4]) -> IoResult<()> {
-        let mut data = [0u8; 4];
-        self.read_exact(&mut data)?;
-        if data != tag {
-            Err(Error::new(ErrorKind::Other, "Tag mismatch"))
-        } else {
-            Ok(())
-        }
-    }
-
     fn take_bytes(&mut self, len: usize) -> IoResult<Vec<u8>> {
         let mut data = vec![0; len];
         self.read_exact(&mut data)?;
@@ -197,7 +1566,12 @@ impl<R: Read> StreamExt for R {
 
 #[cfg(test)]
 mod tests {
-    use super::{parse_header, parse_toc, parse_xcursor, rgba_to_argb, Image, Toc};
+    use super::{
+        convert_bgra, parse_header, parse_toc, parse_xcursor, parse_xcursor_file,
+        parse_xcursor_ref, parse_xcursor_report, parse_xcursor_sized, parse_xcursor_stream,
+        write_xcursor, AlphaMode, ChannelOrder, Comment, CommentKind, ErrorKind, Image, Limit,
+        ParseError, ParseOptions, Strictness, Toc, Warning, XcursorReader,
+    };
     use std::io::Cursor;
 
     // A sample (and simple) XCursor file generated with xcursorgen.
@@ -214,6 +1588,10 @@ mod tests {
         0x00, 0x00, 0x00, 0x80, 0x00, 0x00, 0x00, 0x80,
     ];
 
+    fn to_abgr(bgra: &[u8]) -> Vec<u8> {
+        convert_bgra(bgra, ChannelOrder::Abgr, AlphaMode::Premultiplied)
+    }
+
     #[test]
     fn test_parse_header() {
         let mut cursor = Cursor::new(&FILE_CONTENTS[..]);
@@ -229,7 +1607,7 @@ mod tests {
             pos: 0x1c,
         };
         let mut cursor = Cursor::new(&FILE_CONTENTS[16..]);
-        assert_eq!(parse_toc(&mut cursor).unwrap(), toc);
+        assert_eq!(parse_toc(&mut cursor, 16, 0).unwrap(), toc);
         assert_eq!(cursor.position(), 28 - 16);
     }
 
@@ -240,7 +1618,7 @@ mod tests {
             // This is just "pixels.repeat(4 * 4)", but working in Rust 1.34
             std::iter::repeat(pixel)
                 .take(4 * 4)
-                .flat_map(|p| p.iter().cloned().collect::<Vec<_>>())
+                .flat_map(|p| p.to_vec())
                 .collect()
         };
         let expected = Image {
@@ -250,10 +1628,14 @@ mod tests {
             xhot: 1,
             yhot: 1,
             delay: 1,
-            pixels_rgba: make_pixels([0, 0, 0, 128]),
-            pixels_argb: make_pixels([128, 0, 0, 0]),
+            pixels: make_pixels([0, 0, 0, 128]),
         };
-        assert_eq!(Some(vec![expected]), parse_xcursor(&FILE_CONTENTS));
+        let images = parse_xcursor(&FILE_CONTENTS).unwrap();
+        assert_eq!(
+            images[0].convert_pixels(ChannelOrder::Abgr, AlphaMode::Premultiplied),
+            make_pixels([128, 0, 0, 0])
+        );
+        assert_eq!(vec![expected], images);
     }
 
     #[test]
@@ -292,9 +1674,12 @@ mod tests {
             xhot: 0,
             yhot: 0,
             delay: 0,
-            pixels_rgba: vec![0x12, 0x34, 0x56, 0x78],
-            pixels_argb: vec![0x78, 0x12, 0x34, 0x56],
+            pixels: vec![0x12, 0x34, 0x56, 0x78],
         };
+        assert_eq!(
+            expected.convert_pixels(ChannelOrder::Abgr, AlphaMode::Premultiplied),
+            vec![0x78, 0x12, 0x34, 0x56]
+        );
         assert_eq!(
             Some(vec![expected.clone(), expected.clone(), expected.clone()]),
             parse_xcursor(&data)
@@ -302,23 +1687,525 @@ mod tests {
     }
 
     #[test]
-    fn test_rgba_to_argb() {
+    fn test_parse_ref() {
+        let images = parse_xcursor_ref(&FILE_CONTENTS).unwrap();
+        assert_eq!(images.len(), 1);
+        assert_eq!(images[0].pixels, &FILE_CONTENTS[64..]);
+        assert_eq!(images[0].pixels.as_ptr(), FILE_CONTENTS[64..].as_ptr());
+        assert_eq!(
+            Some(vec![images[0].to_image()]),
+            parse_xcursor(&FILE_CONTENTS)
+        );
+
+        let mut reader = XcursorReader::new(Cursor::new(&FILE_CONTENTS[..])).unwrap();
+        assert_eq!(reader.load_image_refs(32).unwrap(), images);
+
+        let mut reader = XcursorReader::new(Cursor::new(&FILE_CONTENTS[..100])).unwrap();
+        match reader.read_image_ref(0).unwrap_err() {
+            ParseError::TruncatedPixelData {
+                offset: 64,
+                toc_index: 0,
+                expected: 64,
+            } => {}
+            err => panic!("unexpected error: {:?}", err),
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let parse = |data: &[u8]| parse_xcursor_stream(&mut Cursor::new(data)).unwrap_err();
+
+        let mut data = FILE_CONTENTS;
+        data[0] = b'x';
+        match parse(&data) {
+            ParseError::BadMagic { found } => assert_eq!(&found, b"xcur"),
+            err => panic!("unexpected error: {:?}", err),
+        }
+
+        let mut data = FILE_CONTENTS;
+        data[24] = 0xff; // TOC entry 0 points past the end of the file
+        match parse(&data) {
+            ParseError::TocOffsetOutOfBounds {
+                offset: 16,
+                toc_index: 0,
+                position: 0xff,
+                file_len: 128,
+            } => {}
+            err => panic!("unexpected error: {:?}", err),
+        }
+
+        let mut data = FILE_CONTENTS;
+        data[32] = 0x03; // Chunk type
+        match parse(&data) {
+            ParseError::BadChunkHeader {
+                offset: 32,
+                toc_index: 0,
+                expected: 0xfffd_0002,
+                found: 0xfffd_0003,
+            } => {}
+            err => panic!("unexpected error: {:?}", err),
+        }
+
+        let mut data = FILE_CONTENTS;
+        data[45] = 0x80; // Width
+        match parse(&data) {
+            ParseError::ImageTooLarge {
+                offset: 28,
+                toc_index: 0,
+                width: 0x8004,
+                height: 4,
+            } => {}
+            err => panic!("unexpected error: {:?}", err),
+        }
+
+        let mut data = FILE_CONTENTS;
+        data[48] = 0x00; // Height
+        match parse(&data) {
+            ParseError::ZeroSize { height: 0, .. } => {}
+            err => panic!("unexpected error: {:?}", err),
+        }
+
+        let mut data = FILE_CONTENTS;
+        data[52] = 0x05; // X hotspot
+        match parse(&data) {
+            ParseError::HotspotOutsideImage { xhot: 5, .. } => {}
+            err => panic!("unexpected error: {:?}", err),
+        }
+
+        match parse(&FILE_CONTENTS[..100]) {
+            ParseError::TruncatedPixelData {
+                offset: 64,
+                toc_index: 0,
+                expected: 64,
+            } => {}
+            err => panic!("unexpected error: {:?}", err),
+        }
+
+        let err = parse(&FILE_CONTENTS[..20]);
+        assert_eq!((err.offset(), err.toc_index()), (20, Some(0)));
+        match err {
+            ParseError::Io { .. } => {}
+            err => panic!("unexpected error: {:?}", err),
+        }
+    }
+
+    #[test]
+    fn test_write_xcursor() {
+        let images = parse_xcursor(&FILE_CONTENTS).unwrap();
+        let mut output = Vec::new();
+        write_xcursor(&mut output, &images, &[]).unwrap();
+        assert_eq!(&output[..], &FILE_CONTENTS[..]);
+
+        let mut second = images[0].clone();
+        second.size = 8;
+        second.delay = 50;
+        let images = vec![images[0].clone(), second];
+        let comments = vec![
+            Comment {
+                kind: CommentKind::Copyright,
+                text: "(c) Somebody".to_string(),
+            },
+            Comment {
+                kind: CommentKind::License,
+                text: "MIT".to_string(),
+            },
+        ];
+        let mut output = Vec::new();
+        write_xcursor(&mut output, &images, &comments).unwrap();
+        assert_eq!(Some(images.clone()), parse_xcursor(&output));
+        let file = parse_xcursor_file(&output).unwrap();
+        assert_eq!(file.images, images);
+        assert_eq!(file.comments, comments);
+
+        let mut broken = images[0].clone();
+        broken.pixels.pop();
+        assert!(write_xcursor(&mut Vec::new(), &[broken], &[]).is_err());
+
+        // Files the default parser would reject aren't written.
+        let max_images = ParseOptions::default().max_images as usize;
+        let many = vec![images[0].clone(); max_images];
+        let mut output = Vec::new();
+        write_xcursor(&mut output, &many, &[]).unwrap();
+        assert_eq!(
+            parse_xcursor(&output).map(|images| images.len()),
+            Some(max_images)
+        );
+        let too_many = vec![images[0].clone(); max_images + 1];
+        let err = write_xcursor(&mut Vec::new(), &too_many, &[]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_parse_comments() {
+        let data = [
+            b'X', b'c', b'u', b'r', // magic
+            0x10, 0x00, 0x00, 0x00, // header file offset (16)
+            0x00, 0x00, 0x01, 0x00, // version
+            0x02, 0x00, 0x00, 0x00, // num TOC entries, 2
+            // TOC
+            0x01, 0x00, 0xfe, 0xff, // COMMENT_TYPE
+            0x02, 0x00, 0x00, 0x00, // license
+            0x28, 0x00, 0x00, 0x00, // comment offset (40)
+            0x01, 0x00, 0xfe, 0xff, // COMMENT_TYPE
+            0x2a, 0x00, 0x00, 0x00, // unknown subtype
+            0x3f, 0x00, 0x00, 0x00, // comment offset (63)
+            // comment
+            0x14, 0x00, 0x00, 0x00, // header
+            0x01, 0x00, 0xfe, 0xff, // COMMENT_TYPE
+            0x02, 0x00, 0x00, 0x00, // license
+            0x01, 0x00, 0x00, 0x00, // version
+            0x03, 0x00, 0x00, 0x00, // length 3
+            b'M', b'I', b'T', // text
+            // comment
+            0x14, 0x00, 0x00, 0x00, // header
+            0x01, 0x00, 0xfe, 0xff, // COMMENT_TYPE
+            0x2a, 0x00, 0x00, 0x00, // unknown subtype
+            0x01, 0x00, 0x00, 0x00, // version
+            0x00, 0x00, 0x00, 0x00, // length 0
+        ];
+
+        let file = parse_xcursor_file(&data).unwrap();
+        assert!(file.images.is_empty());
+        assert_eq!(
+            file.comments,
+            vec![
+                Comment {
+                    kind: CommentKind::License,
+                    text: "MIT".to_string(),
+                },
+                Comment {
+                    kind: CommentKind::Unknown(0x2a),
+                    text: String::new(),
+                },
+            ]
+        );
+
+        // Comments aren't needed to load the images
+        let mut truncated = data[..62].to_vec();
+        truncated[12] = 0x01; // num TOC entries, 1
+        assert_eq!(parse_xcursor(&truncated), Some(vec![]));
+        match parse_xcursor_file(&truncated).unwrap_err() {
+            ParseError::TruncatedComment {
+                offset: 60,
+                toc_index: 0,
+                expected: 3,
+            } => {}
+            err => panic!("unexpected error: {:?}", err),
+        }
+    }
+
+    #[test]
+    fn test_reader() {
+        let mut second = parse_xcursor(&FILE_CONTENTS).unwrap().remove(0);
+        second.size = 8;
+        let images = vec![second.clone(), second.clone()];
+        let comments = vec![Comment {
+            kind: CommentKind::Other,
+            text: "Hello".to_string(),
+        }];
+        let mut data = Vec::new();
+        write_xcursor(&mut data, &images, &comments).unwrap();
+
+        let mut reader = XcursorReader::new(Cursor::new(&data[..])).unwrap();
+        assert_eq!(reader.toc().len(), 3);
+        assert!(reader.toc()[1].is_image());
+        assert!(reader.toc()[2].is_comment());
+        assert_eq!(reader.nominal_sizes(), vec![8]);
+        assert_eq!(reader.read_comment(2).unwrap(), comments[0]);
+        assert_eq!(reader.read_image(1).unwrap(), second);
+        match reader.read_image(2).unwrap_err() {
+            ParseError::BadChunkHeader {
+                toc_index: 2,
+                expected: 0x24,
+                found: 0x14,
+                ..
+            } => {}
+            err => panic!("unexpected error: {:?}", err),
+        }
+    }
+
+    #[test]
+    fn test_best_size() {
+        let base = parse_xcursor(&FILE_CONTENTS).unwrap().remove(0);
+        let image = |size, delay| Image {
+            size,
+            delay,
+            ..base.clone()
+        };
+        let images = vec![
+            image(24, 1),
+            image(0, 1),
+            image(32, 1),
+            image(24, 2),
+            image(16, 1),
+            image(32, 2),
+        ];
+        let mut data = Vec::new();
+        write_xcursor(&mut data, &images, &[]).unwrap();
+
+        let reader = XcursorReader::new(Cursor::new(&data[..])).unwrap();
+        assert_eq!(reader.best_size(0), Some(16));
+        assert_eq!(reader.best_size(24), Some(24));
+        assert_eq!(reader.best_size(28), Some(24)); // Tie, 24 comes first
+        assert_eq!(reader.best_size(30), Some(32));
+        assert_eq!(reader.best_size(1000), Some(32));
+
+        let sized = parse_xcursor_sized(&data, 26).unwrap();
+        assert_eq!(sized, vec![image(24, 1), image(24, 2)]);
+        let sized = parse_xcursor_sized(&data, 17).unwrap();
+        assert_eq!(sized, vec![image(16, 1)]);
+
+        let mut data = Vec::new();
+        write_xcursor(&mut data, &[image(0, 1)], &[]).unwrap();
+        assert_eq!(parse_xcursor_sized(&data, 0).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_limits() {
+        // A 16-byte file claiming 4 billion TOC entries
+        let data = [
+            b'X', b'c', b'u', b'r', // magic
+            0x10, 0x00, 0x00, 0x00, // header file offset (16)
+            0x00, 0x00, 0x01, 0x00, // version
+            0xff, 0xff, 0xff, 0xff, // num TOC entries
+        ];
+        match parse_xcursor_stream(&mut Cursor::new(&data[..])).unwrap_err() {
+            ParseError::LimitExceeded {
+                offset: 12,
+                toc_index: None,
+                limit: Limit::TocEntries,
+                value: 0xffff_ffff,
+            } => {}
+            err => panic!("unexpected error: {:?}", err),
+        }
+
+        // An image claiming to be 0x7fff x 0x7fff, in a tiny file
+        let mut data = FILE_CONTENTS;
+        data[44..52].copy_from_slice(&[0xff, 0x7f, 0, 0, 0xff, 0x7f, 0, 0]);
+        match parse_xcursor_stream(&mut Cursor::new(&data[..])).unwrap_err() {
+            ParseError::TruncatedPixelData { offset: 64, .. } => {}
+            err => panic!("unexpected error: {:?}", err),
+        }
+
+        let image = parse_xcursor(&FILE_CONTENTS).unwrap().remove(0);
+        let mut data = Vec::new();
+        write_xcursor(&mut data, &[image.clone(), image.clone(), image], &[]).unwrap();
+        let parse = |options: &ParseOptions| {
+            parse_xcursor_report(&mut Cursor::new(&data[..]), options).map(|report| report.file)
+        };
+
+        let options = ParseOptions {
+            max_images: 2,
+            ..Default::default()
+        };
+        match parse(&options).unwrap_err() {
+            ParseError::LimitExceeded {
+                limit: Limit::Images,
+                value: 3,
+                ..
+            } => {}
+            err => panic!("unexpected error: {:?}", err),
+        }
+
+        let options = ParseOptions {
+            max_pixel_bytes: 128,
+            ..Default::default()
+        };
+        match parse(&options).unwrap_err() {
+            ParseError::LimitExceeded {
+                toc_index: Some(2),
+                limit: Limit::PixelBytes,
+                value: 192,
+                ..
+            } => {}
+            err => panic!("unexpected error: {:?}", err),
+        }
+
+        let options = ParseOptions {
+            max_toc_entries: 3,
+            max_images: 3,
+            max_pixel_bytes: 192,
+            ..Default::default()
+        };
+        assert_eq!(parse(&options).unwrap().images.len(), 3);
+    }
+
+    #[test]
+    fn test_chunk_header_validation() {
+        let data = [
+            b'X', b'c', b'u', b'r', // magic
+            0x10, 0x00, 0x00, 0x00, // header file offset (16)
+            0x00, 0x00, 0x01, 0x00, // version
+            0x01, 0x00, 0x00, 0x00, // num TOC entries, 1
+            // TOC
+            0x02, 0x00, 0xfd, 0xff, // IMAGE_TYPE
+            0x03, 0x00, 0x00, 0x00, // size 3
+            0x1c, 0x00, 0x00, 0x00, // image offset (28)
+            // image
+            0x28, 0x00, 0x00, 0x00, // header (40)
+            0x02, 0x00, 0xfd, 0xff, // IMAGE_TYPE
+            0x04, 0x00, 0x00, 0x00, // size 4
+            0x02, 0x00, 0x00, 0x00, // version 2
+            0x01, 0x00, 0x00, 0x00, // width 1
+            0x01, 0x00, 0x00, 0x00, // height 1
+            0x00, 0x00, 0x00, 0x00, // x_hot 0
+            0x00, 0x00, 0x00, 0x00, // y_hot 0
+            0x00, 0x00, 0x00, 0x00, // delay 0
+            0xde, 0xad, 0xbe, 0xef, // unknown field
+            0x12, 0x34, 0x56, 0x78, // pixel
+        ];
+
+        let mut reader = XcursorReader::new(Cursor::new(&data[..])).unwrap();
+        let image = reader.read_image(0).unwrap();
+        assert_eq!(image.size, 4);
+        assert_eq!(image.pixels, vec![0x12, 0x34, 0x56, 0x78]);
+        assert_eq!(
+            reader.warnings(),
+            &[
+                Warning::LongChunkHeader {
+                    offset: 28,
+                    toc_index: 0,
+                    header_len: 40,
+                },
+                Warning::SubtypeMismatch {
+                    offset: 36,
+                    toc_index: 0,
+                    toc_subtype: 3,
+                    chunk_subtype: 4,
+                },
+                Warning::UnknownVersion {
+                    offset: 40,
+                    toc_index: 0,
+                    version: 2,
+                },
+            ][..]
+        );
+
+        let options = ParseOptions {
+            strictness: Strictness::Strict,
+            ..Default::default()
+        };
+        match parse_xcursor_report(&mut Cursor::new(&data[..]), &options).unwrap_err() {
+            ParseError::StrictViolation(Warning::LongChunkHeader { .. }) => {}
+            err => panic!("unexpected error: {:?}", err),
+        }
+
+        // The header can't be shorter than the known fields
+        let mut data = data;
+        data[28] = 0x20;
+        match parse_xcursor_stream(&mut Cursor::new(&data[..])).unwrap_err() {
+            ParseError::BadChunkHeader {
+                offset: 28,
+                expected: 36,
+                found: 32,
+                ..
+            } => {}
+            err => panic!("unexpected error: {:?}", err),
+        }
+    }
+
+    #[test]
+    fn test_lenient_mode() {
+        let image = parse_xcursor(&FILE_CONTENTS).unwrap().remove(0);
+        let mut data = Vec::new();
+        write_xcursor(
+            &mut data,
+            &[image.clone(), image.clone(), image.clone()],
+            &[],
+        )
+        .unwrap();
+        // Second image: zero width. Third image: TOC entry out of bounds.
+        data[52 + 100 + 16] = 0;
+        data[16 + 2 * 12 + 11] = 0xff;
+
+        let options = |strictness| ParseOptions {
+            strictness,
+            ..Default::default()
+        };
+
+        let report =
+            parse_xcursor_report(&mut Cursor::new(&data[..]), &options(Strictness::Lenient))
+                .unwrap();
+        assert_eq!(report.file.images, vec![image.clone()]);
+        assert!(report.warnings.is_empty());
+        assert_eq!(report.skipped.len(), 2);
+        match report.skipped[0] {
+            ParseError::ZeroSize { toc_index: 1, .. } => {}
+            ref err => panic!("unexpected error: {:?}", err),
+        }
+        match report.skipped[1] {
+            ParseError::TocOffsetOutOfBounds { toc_index: 2, .. } => {}
+            ref err => panic!("unexpected error: {:?}", err),
+        }
+
+        let mut reader =
+            XcursorReader::with_options(Cursor::new(&data[..]), options(Strictness::Lenient))
+                .unwrap();
+        assert_eq!(reader.load_images(4).unwrap(), vec![image]);
+        assert_eq!(reader.skipped().len(), 2);
+
+        match parse_xcursor_report(&mut Cursor::new(&data[..]), &options(Strictness::Normal))
+            .unwrap_err()
+        {
+            ParseError::ZeroSize { toc_index: 1, .. } => {}
+            err => panic!("unexpected error: {:?}", err),
+        }
+    }
+
+    #[test]
+    fn test_convert_to_abgr() {
         let initial: [u8; 8] = [0, 1, 2, 3, 4, 5, 6, 7];
 
-        assert_eq!(rgba_to_argb(&initial), [3u8, 0, 1, 2, 7, 4, 5, 6])
+        assert_eq!(to_abgr(&initial), [3u8, 0, 1, 2, 7, 4, 5, 6])
     }
 
     #[test]
-    fn test_rgba_to_argb_extra_items() {
+    fn test_convert_to_abgr_extra_items() {
         let initial: [u8; 9] = [0, 1, 2, 3, 4, 5, 6, 7, 8];
 
-        assert_eq!(rgba_to_argb(&initial), &[3u8, 0, 1, 2, 7, 4, 5, 6]);
+        assert_eq!(to_abgr(&initial), &[3u8, 0, 1, 2, 7, 4, 5, 6]);
     }
 
     #[test]
-    fn test_rgba_to_argb_no_items() {
+    fn test_convert_to_abgr_no_items() {
         let initial: &[u8] = &[];
 
-        assert_eq!(initial, &rgba_to_argb(initial)[..]);
+        assert_eq!(initial, &to_abgr(initial)[..]);
+    }
+
+    #[test]
+    fn test_convert_pixels() {
+        let image = Image {
+            size: 2,
+            width: 2,
+            height: 1,
+            xhot: 0,
+            yhot: 0,
+            delay: 0,
+            // Half-transparent red, and a fully transparent pixel
+            pixels: vec![0x00, 0x00, 0x80, 0x80, 0x00, 0x00, 0x00, 0x00],
+        };
+
+        assert_eq!(image.pixels_argb32(), vec![0x8080_0000, 0]);
+
+        let convert = |order| image.convert_pixels(order, AlphaMode::Premultiplied);
+        assert_eq!(convert(ChannelOrder::Bgra), image.pixels);
+        assert_eq!(
+            convert(ChannelOrder::Abgr),
+            vec![0x80, 0, 0, 0x80, 0, 0, 0, 0]
+        );
+        assert_eq!(
+            convert(ChannelOrder::Rgba),
+            vec![0x80, 0, 0, 0x80, 0, 0, 0, 0]
+        );
+        assert_eq!(
+            convert(ChannelOrder::Argb),
+            vec![0x80, 0x80, 0, 0, 0, 0, 0, 0]
+        );
+
+        assert_eq!(
+            image.convert_pixels(ChannelOrder::Rgba, AlphaMode::Straight),
+            vec![0xff, 0, 0, 0x80, 0, 0, 0, 0]
+        );
     }
 }
@@ -0,0 +1,172 @@
+use crate::names;
+use crate::{CursorTheme, ThemeCursor};
+use std::path::PathBuf;
+
+/// How a cursor was looked up in a theme, to explain which file was picked.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Resolution {
+    /// The themes that were searched, in order. The last one is the theme
+    /// providing the cursor, if it was found.
+    pub themes: Vec<ResolvedTheme>,
+    /// Every file that was looked for, in order.
+    pub probes: Vec<Probe>,
+    /// The cursor that was found, if any.
+    pub cursor: Option<ThemeCursor>,
+}
+
+/// A theme searched while resolving a cursor.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ResolvedTheme {
+    /// The name of the theme.
+    pub name: String,
+    /// How many levels of inheritance separate it from the loaded theme.
+    pub depth: usize,
+    /// The directories of the theme. Empty if the theme is not installed in
+    /// any search path.
+    pub directories: Vec<PathBuf>,
+}
+
+/// A cursor file looked for while resolving a cursor.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Probe {
+    /// The theme the file belongs to.
+    pub theme: String,
+    /// The path of the file.
+    pub path: PathBuf,
+    /// Whether the file exists.
+    pub found: bool,
+}
+
+impl CursorTheme {
+    /// Look up a cursor like [`load_icon_with_depth`](#method.load_icon_with_depth),
+    /// recording the themes and files that were searched.
+    pub fn resolve(&self, icon_name: &str) -> Resolution {
+        self.resolve_names(&[icon_name])
+    }
+
+    /// Look up a cursor like
+    /// [`load_icon_with_aliases`](#method.load_icon_with_aliases), recording
+    /// the themes and files that were searched.
+    pub fn resolve_with_aliases(&self, name: &str) -> Resolution {
+        self.resolve_names(&names::lookup_names(name))
+    }
+
+    /// The implementation of cursor resolution.
+    fn resolve_names(&self, icon_names: &[&str]) -> Resolution {
+        let mut resolution = Resolution {
+            themes: Vec::new(),
+            probes: Vec::new(),
+            cursor: None,
+        };
+
+        for (theme, depth) in self.theme.chain(&self.search_paths) {
+            resolution.themes.push(ResolvedTheme {
+                name: theme.name.clone(),
+                depth,
+                directories: theme.data.iter().map(|data| data.0.clone()).collect(),
+            });
+
+            for icon_name in icon_names {
+                for data in &theme.data {
+                    let path = data.0.join("cursors").join(icon_name);
+                    let found = path.is_file();
+                    resolution.probes.push(Probe {
+                        theme: theme.name.clone(),
+                        path: path.clone(),
+                        found,
+                    });
+
+                    if found {
+                        resolution.cursor = Some(ThemeCursor {
+                            name: icon_name.to_string(),
+                            theme: theme.name.clone(),
+                            path,
+                            depth,
+                        });
+                        return resolution;
+                    }
+                }
+            }
+        }
+
+        resolution
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Probe, ResolvedTheme};
+    use crate::test_util::TempDir;
+    use crate::{CursorTheme, ThemeCursor};
+
+    #[test]
+    fn test_resolve() {
+        let user = TempDir::new("resolve-user");
+        let system = TempDir::new("resolve-system");
+        let child = user.add_theme("child", Some("Inherits=missing, parent"), &[]);
+        let parent = system.add_theme("parent", Some("Inherits=none"), &["xterm"]);
+
+        let search_paths = vec![user.0.clone(), system.0.clone()];
+        let theme = CursorTheme::load_with_search_paths("child", search_paths);
+
+        let resolution = theme.resolve_with_aliases("text");
+        assert_eq!(
+            resolution.themes,
+            vec![
+                ResolvedTheme {
+                    name: String::from("child"),
+                    depth: 0,
+                    directories: vec![child.clone()],
+                },
+                ResolvedTheme {
+                    name: String::from("missing"),
+                    depth: 1,
+                    directories: vec![],
+                },
+                ResolvedTheme {
+                    name: String::from("parent"),
+                    depth: 1,
+                    directories: vec![parent.clone()],
+                },
+            ]
+        );
+        assert_eq!(
+            resolution.probes[..2],
+            [
+                Probe {
+                    theme: String::from("child"),
+                    path: child.join("cursors/text"),
+                    found: false,
+                },
+                Probe {
+                    theme: String::from("child"),
+                    path: child.join("cursors/xterm"),
+                    found: false,
+                },
+            ]
+        );
+        assert_eq!(
+            resolution.probes.last(),
+            Some(&Probe {
+                theme: String::from("parent"),
+                path: parent.join("cursors/xterm"),
+                found: true,
+            })
+        );
+        assert_eq!(
+            resolution.cursor,
+            Some(ThemeCursor {
+                name: String::from("xterm"),
+                theme: String::from("parent"),
+                path: parent.join("cursors/xterm"),
+                depth: 1,
+            })
+        );
+
+        let resolution = theme.resolve("text");
+        assert_eq!(resolution.themes.len(), 4);
+        assert_eq!(resolution.probes.len(), 2);
+        assert!(resolution.probes.iter().all(|probe| !probe.found));
+        assert_eq!(resolution.cursor, None);
+    }
+}
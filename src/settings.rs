@@ -0,0 +1,336 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The theme used when no setting is found.
+pub const DEFAULT_THEME: &str = "default";
+
+/// The size used when no setting is found, in pixels.
+pub const DEFAULT_SIZE: u32 = 24;
+
+/// Where a setting was read from.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum SettingSource {
+    /// The `XCURSOR_THEME` or `XCURSOR_SIZE` environment variable.
+    Environment,
+    /// The `Xcursor.theme` or `Xcursor.size` resource of an X resources
+    /// file, like `~/.Xresources`.
+    Xresources(PathBuf),
+    /// The `gtk-cursor-theme-name` or `gtk-cursor-theme-size` key of a GTK
+    /// `settings.ini` file.
+    Gtk(PathBuf),
+    /// The `cursorTheme` or `cursorSize` key of KDE's `kcminputrc` file.
+    Kde(PathBuf),
+    /// No setting was found, and the default value is used.
+    Default,
+}
+
+/// The cursor theme and size configured by the user.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CursorSettings {
+    /// The name of the theme, to pass to
+    /// [`CursorTheme::load`](../struct.CursorTheme.html#method.load).
+    pub theme: String,
+    /// Where the theme name was read from.
+    pub theme_source: SettingSource,
+    /// The nominal size of the cursors, in pixels.
+    pub size: u32,
+    /// Where the size was read from.
+    pub size_source: SettingSource,
+}
+
+/// Determine the cursor theme and size configured by the user.
+///
+/// The theme and the size are each read from the first of these sources
+/// that sets them:
+///
+/// 1. The `XCURSOR_THEME` and `XCURSOR_SIZE` environment variables, which
+///    libXcursor and libwayland-cursor honor.
+/// 2. The `gtk-cursor-theme-name` and `gtk-cursor-theme-size` keys in the
+///    `[Settings]` section of `$XDG_CONFIG_HOME/gtk-4.0/settings.ini`, then
+///    of `$XDG_CONFIG_HOME/gtk-3.0/settings.ini`.
+/// 3. The `cursorTheme` and `cursorSize` keys in the `[Mouse]` section of
+///    `$XDG_CONFIG_HOME/kcminputrc`.
+/// 4. The `Xcursor.theme` and `Xcursor.size` resources in `~/.Xresources`,
+///    then in `~/.Xdefaults`. These come last because they are only read by
+///    X11 clients, and are often left over from older setups.
+///
+/// Since the theme and the size are resolved separately, they may come from
+/// different sources, e.g. a theme set in GTK's settings with a size only
+/// set in `kcminputrc`. Check `theme_source` and `size_source` to tell.
+///
+/// `XDG_CONFIG_HOME` defaults to `~/.config`. Sizes which are not positive
+/// integers are ignored. If no source sets them, the theme is
+/// [`DEFAULT_THEME`](constant.DEFAULT_THEME.html) and the size is
+/// [`DEFAULT_SIZE`](constant.DEFAULT_SIZE.html).
+pub fn cursor_settings() -> CursorSettings {
+    resolve_settings(&SettingsEnvironment::get())
+}
+
+#[derive(Default)]
+struct SettingsEnvironment {
+    home: Option<String>,
+    xdg_config_home: Option<String>,
+    xcursor_theme: Option<String>,
+    xcursor_size: Option<String>,
+}
+
+impl SettingsEnvironment {
+    fn get() -> Self {
+        SettingsEnvironment {
+            home: env::var("HOME").ok().filter(|x| !x.is_empty()),
+            xdg_config_home: env::var("XDG_CONFIG_HOME").ok().filter(|x| !x.is_empty()),
+            xcursor_theme: env::var("XCURSOR_THEME").ok().filter(|x| !x.is_empty()),
+            xcursor_size: env::var("XCURSOR_SIZE").ok().filter(|x| !x.is_empty()),
+        }
+    }
+}
+
+/// A source of settings, with the theme and size it sets, if any.
+struct Found {
+    source: SettingSource,
+    theme: Option<String>,
+    size: Option<u32>,
+}
+
+/// The implementation of setting resolution.
+fn resolve_settings(environment: &SettingsEnvironment) -> CursorSettings {
+    let mut sources = vec![Found {
+        source: SettingSource::Environment,
+        theme: environment.xcursor_theme.clone(),
+        size: environment
+            .xcursor_size
+            .as_ref()
+            .and_then(|size| parse_size(size)),
+    }];
+
+    let home = environment.home.as_ref().map(PathBuf::from);
+    let config_home = environment
+        .xdg_config_home
+        .as_ref()
+        .map(PathBuf::from)
+        .or_else(|| home.as_ref().map(|home| home.join(".config")));
+
+    if let Some(ref config_home) = config_home {
+        for dir in &["gtk-4.0", "gtk-3.0"] {
+            let path = config_home.join(dir).join("settings.ini");
+            if let Some(content) = read(&path) {
+                sources.push(Found {
+                    theme: ini_value(&content, "Settings", "gtk-cursor-theme-name"),
+                    size: ini_value(&content, "Settings", "gtk-cursor-theme-size")
+                        .and_then(|size| parse_size(&size)),
+                    source: SettingSource::Gtk(path),
+                });
+            }
+        }
+
+        let path = config_home.join("kcminputrc");
+        if let Some(content) = read(&path) {
+            sources.push(Found {
+                theme: ini_value(&content, "Mouse", "cursorTheme"),
+                size: ini_value(&content, "Mouse", "cursorSize").and_then(|size| parse_size(&size)),
+                source: SettingSource::Kde(path),
+            });
+        }
+    }
+
+    if let Some(ref home) = home {
+        for name in &[".Xresources", ".Xdefaults"] {
+            let path = home.join(name);
+            if let Some(content) = read(&path) {
+                sources.push(Found {
+                    theme: xresource_value(&content, "Xcursor.theme"),
+                    size: xresource_value(&content, "Xcursor.size")
+                        .and_then(|size| parse_size(&size)),
+                    source: SettingSource::Xresources(path),
+                });
+            }
+        }
+    }
+
+    let (theme, theme_source) = sources
+        .iter()
+        .find_map(|found| {
+            found
+                .theme
+                .clone()
+                .map(|theme| (theme, found.source.clone()))
+        })
+        .unwrap_or_else(|| (String::from(DEFAULT_THEME), SettingSource::Default));
+    let (size, size_source) = sources
+        .iter()
+        .find_map(|found| found.size.map(|size| (size, found.source.clone())))
+        .unwrap_or((DEFAULT_SIZE, SettingSource::Default));
+
+    CursorSettings {
+        theme,
+        theme_source,
+        size,
+        size_source,
+    }
+}
+
+fn read(path: &Path) -> Option<String> {
+    fs::read_to_string(path).ok()
+}
+
+/// Parse a size, which must be a positive integer.
+fn parse_size(size: &str) -> Option<u32> {
+    size.trim().parse().ok().filter(|&size| size > 0)
+}
+
+/// Get the non-empty value of a resource in an X resources file. The last
+/// definition wins, like with `xrdb`.
+fn xresource_value(content: &str, name: &str) -> Option<String> {
+    content
+        .lines()
+        .rev()
+        .filter(|line| !line.trim_start().starts_with('!'))
+        .filter_map(|line| {
+            let colon = line.find(':')?;
+            if line[..colon].trim() != name {
+                return None;
+            }
+            Some(line[colon + 1..].trim().to_string())
+        })
+        .find(|value| !value.is_empty())
+}
+
+/// Get the non-empty value of a key in a section of an INI-style file.
+fn ini_value(content: &str, section: &str, key: &str) -> Option<String> {
+    let mut in_section = false;
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_section = line == format!("[{}]", section);
+            continue;
+        }
+        if !in_section || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+
+        let eq = match line.find('=') {
+            Some(eq) => eq,
+            None => continue,
+        };
+        let value = line[eq + 1..].trim();
+        if line[..eq].trim() == key && !value.is_empty() {
+            return Some(value.to_string());
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ini_value, resolve_settings, xresource_value, CursorSettings, SettingSource,
+        SettingsEnvironment,
+    };
+    use crate::test_util::TempDir;
+    use std::fs;
+
+    #[test]
+    fn test_parse_settings() {
+        let xresources = "! Xcursor.theme: Commented\nXcursor.theme:\tBreeze\nXcursor.size : 32\nXcursor.theme: Adwaita\n";
+        assert_eq!(
+            xresource_value(xresources, "Xcursor.theme"),
+            Some(String::from("Adwaita"))
+        );
+        assert_eq!(
+            xresource_value(xresources, "Xcursor.size"),
+            Some(String::from("32"))
+        );
+        assert_eq!(xresource_value("Xcursor.theme:", "Xcursor.theme"), None);
+
+        let ini = "cursorTheme=Outside\n[Mouse]\n# cursorTheme=Commented\ncursorTheme = Breeze\n[Other]\ncursorSize=48\n";
+        assert_eq!(
+            ini_value(ini, "Mouse", "cursorTheme"),
+            Some(String::from("Breeze"))
+        );
+        assert_eq!(ini_value(ini, "Mouse", "cursorSize"), None);
+    }
+
+    #[test]
+    fn test_resolve_settings() {
+        let dir = TempDir::new("settings");
+        let home = dir.0.join("home");
+        let config = home.join(".config");
+        fs::create_dir_all(config.join("gtk-3.0")).unwrap();
+        fs::write(
+            config.join("gtk-3.0/settings.ini"),
+            "[Settings]\ngtk-cursor-theme-name=Adwaita\ngtk-cursor-theme-size=0\n",
+        )
+        .unwrap();
+        fs::write(
+            config.join("kcminputrc"),
+            "[Mouse]\ncursorTheme=Breeze\ncursorSize=36\n",
+        )
+        .unwrap();
+
+        let environment = SettingsEnvironment {
+            home: Some(home.to_string_lossy().into_owned()),
+            ..Default::default()
+        };
+        // The theme and the size come from different sources, as GTK's size
+        // is invalid.
+        assert_eq!(
+            resolve_settings(&environment),
+            CursorSettings {
+                theme: String::from("Adwaita"),
+                theme_source: SettingSource::Gtk(config.join("gtk-3.0/settings.ini")),
+                size: 36,
+                size_source: SettingSource::Kde(config.join("kcminputrc")),
+            }
+        );
+
+        // X resources don't override the desktop's settings.
+        fs::write(
+            home.join(".Xresources"),
+            "Xcursor.theme: Leftover\nXcursor.size: 40\n",
+        )
+        .unwrap();
+        let environment = SettingsEnvironment {
+            home: Some(home.to_string_lossy().into_owned()),
+            xcursor_theme: Some(String::from("DMZ-White")),
+            xcursor_size: Some(String::from("big")),
+            ..Default::default()
+        };
+        assert_eq!(
+            resolve_settings(&environment),
+            CursorSettings {
+                theme: String::from("DMZ-White"),
+                theme_source: SettingSource::Environment,
+                size: 36,
+                size_source: SettingSource::Kde(config.join("kcminputrc")),
+            }
+        );
+
+        fs::remove_file(config.join("kcminputrc")).unwrap();
+        let environment = SettingsEnvironment {
+            home: Some(home.to_string_lossy().into_owned()),
+            ..Default::default()
+        };
+        assert_eq!(
+            resolve_settings(&environment),
+            CursorSettings {
+                theme: String::from("Adwaita"),
+                theme_source: SettingSource::Gtk(config.join("gtk-3.0/settings.ini")),
+                size: 40,
+                size_source: SettingSource::Xresources(home.join(".Xresources")),
+            }
+        );
+
+        assert_eq!(
+            resolve_settings(&SettingsEnvironment::default()),
+            CursorSettings {
+                theme: String::from("default"),
+                theme_source: SettingSource::Default,
+                size: 24,
+                size_source: SettingSource::Default,
+            }
+        );
+    }
+}
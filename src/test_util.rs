@@ -0,0 +1,65 @@
+use crate::parser::{write_xcursor, Image};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A 2x2 transparent image with its hotspot at the top left corner.
+pub fn image(size: u32, delay: u32) -> Image {
+    Image {
+        size,
+        width: 2,
+        height: 2,
+        xhot: 0,
+        yhot: 0,
+        delay,
+        pixels: vec![0; 16],
+    }
+}
+
+/// Write an XCursor file holding the given images.
+pub fn write_cursor(path: &Path, images: &[Image]) {
+    let mut content = Vec::new();
+    write_xcursor(&mut content, images, &[]).unwrap();
+    fs::write(path, content).unwrap();
+}
+
+/// A temporary directory holding cursor themes, removed when dropped.
+pub struct TempDir(pub PathBuf);
+
+impl TempDir {
+    pub fn new(name: &str) -> TempDir {
+        let path = env::temp_dir().join(format!("xcursor-rs-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        TempDir(path)
+    }
+
+    /// Create a theme with the given `[Icon Theme]` entries in its `index.theme` and cursor
+    /// files.
+    pub fn add_theme(&self, name: &str, index: Option<&str>, cursors: &[&str]) -> PathBuf {
+        let theme_dir = self.0.join(name);
+        fs::create_dir_all(theme_dir.join("cursors")).unwrap();
+        if let Some(index) = index {
+            let content = format!("[Icon Theme]\n{}\n", index);
+            fs::write(theme_dir.join("index.theme"), content).unwrap();
+        }
+        for cursor in cursors {
+            fs::write(theme_dir.join("cursors").join(cursor), b"").unwrap();
+        }
+        theme_dir
+    }
+
+    /// Create a theme with an `index.theme` file but no `cursors` directory.
+    pub fn add_icon_theme(&self, name: &str, index: &str) -> PathBuf {
+        let theme_dir = self.0.join(name);
+        fs::create_dir_all(&theme_dir).unwrap();
+        fs::write(theme_dir.join("index.theme"), index).unwrap();
+        theme_dir
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
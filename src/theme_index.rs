@@ -0,0 +1,283 @@
+use std::io;
+use std::path::Path;
+
+/// The parsed `[Icon Theme]` section of a theme's `index.theme` file, as
+/// described by the XDG Icon Theme specification.
+///
+/// Keys can be localized, as in `Name[de]=...`; the accessors taking a
+/// `locale` follow the matching rules of the Desktop Entry specification,
+/// falling back to the unlocalized value.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct ThemeIndex {
+    /// The entries of the `[Icon Theme]` section, in file order.
+    entries: Vec<Entry>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+struct Entry {
+    key: String,
+    locale: Option<String>,
+    value: String,
+}
+
+impl ThemeIndex {
+    /// Parse the content of an `index.theme` file.
+    ///
+    /// Malformed lines are ignored, and so are all sections other than
+    /// `[Icon Theme]`.
+    pub fn parse(content: &str) -> ThemeIndex {
+        let mut entries = Vec::new();
+        let mut in_section = false;
+
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if line.starts_with('[') {
+                in_section = line == "[Icon Theme]";
+                continue;
+            }
+            if !in_section {
+                continue;
+            }
+
+            if let Some(entry) = parse_entry(line) {
+                entries.push(entry);
+            }
+        }
+
+        ThemeIndex { entries }
+    }
+
+    /// Read and parse an `index.theme` file.
+    pub fn load(path: &Path) -> io::Result<ThemeIndex> {
+        let content = std::fs::read_to_string(path)?;
+
+        Ok(ThemeIndex::parse(&content))
+    }
+
+    /// Get the unlocalized value of a key. If the key appears several times,
+    /// the first one wins.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|entry| entry.key == key && entry.locale.is_none())
+            .map(|entry| entry.value.as_str())
+    }
+
+    /// Get the value of a key for the given locale, such as `de_DE.UTF-8`.
+    ///
+    /// For a locale of the form `lang_COUNTRY@MODIFIER`, the values for
+    /// `lang_COUNTRY@MODIFIER`, `lang_COUNTRY`, `lang@MODIFIER` and `lang`
+    /// are tried in this order, and then the unlocalized value.
+    pub fn get_localized(&self, key: &str, locale: &str) -> Option<&str> {
+        for candidate in locale_candidates(locale) {
+            let found = self.entries.iter().find(|entry| {
+                entry.key == key && entry.locale.as_ref().map(String::as_str) == Some(&candidate)
+            });
+            if let Some(entry) = found {
+                return Some(&entry.value);
+            }
+        }
+
+        self.get(key)
+    }
+
+    /// The display name of the theme.
+    pub fn name(&self) -> Option<&str> {
+        self.get("Name")
+    }
+
+    /// The display name of the theme, in the given locale.
+    pub fn localized_name(&self, locale: &str) -> Option<&str> {
+        self.get_localized("Name", locale)
+    }
+
+    /// A short description of the theme.
+    pub fn comment(&self) -> Option<&str> {
+        self.get("Comment")
+    }
+
+    /// A short description of the theme, in the given locale.
+    pub fn localized_comment(&self, locale: &str) -> Option<&str> {
+        self.get_localized("Comment", locale)
+    }
+
+    /// The name of the cursor to show when previewing the theme.
+    pub fn example(&self) -> Option<&str> {
+        self.get("Example")
+    }
+
+    /// The names of the themes this theme inherits from, in order.
+    ///
+    /// Like libXcursor, names can be separated by commas, semicolons or
+    /// whitespace. Unlike libXcursor, which cursor theme lookups follow, only
+    /// the `[Icon Theme]` section is read.
+    pub fn inherits(&self) -> Vec<String> {
+        self.get("Inherits")
+            .map(|inherits| {
+                inherits
+                    .split(|ch: char| ch.is_whitespace() || ch == ';' || ch == ',')
+                    .filter(|name| !name.is_empty())
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Whether the theme should be hidden from users, e.g. because it only
+    /// exists to be inherited from.
+    pub fn hidden(&self) -> bool {
+        self.get("Hidden") == Some("true")
+    }
+}
+
+/// Parse a `Key=Value` or `Key[locale]=Value` line.
+fn parse_entry(line: &str) -> Option<Entry> {
+    let eq = line.find('=')?;
+    let (key, value) = (line[..eq].trim_end(), line[eq + 1..].trim_start());
+
+    let (key, locale) = match key.find('[') {
+        Some(open) if key.ends_with(']') => {
+            (&key[..open], Some(key[open + 1..key.len() - 1].to_string()))
+        }
+        Some(_) => return None,
+        None => (key, None),
+    };
+
+    let is_key_char = |ch: char| ch.is_ascii_alphanumeric() || ch == '-';
+    if key.is_empty() || !key.chars().all(is_key_char) {
+        return None;
+    }
+
+    Some(Entry {
+        key: key.to_string(),
+        locale,
+        value: unescape(value),
+    })
+}
+
+/// Replace the escape sequences allowed in string values.
+fn unescape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            result.push(ch);
+            continue;
+        }
+
+        match chars.next() {
+            Some('s') => result.push(' '),
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some('r') => result.push('\r'),
+            Some(other) => result.push(other),
+            None => result.push('\\'),
+        }
+    }
+
+    result
+}
+
+/// The locale names to try, in order, when looking up a localized key.
+fn locale_candidates(locale: &str) -> Vec<String> {
+    // The encoding is ignored when matching.
+    let locale = match locale.find('.') {
+        Some(dot) => {
+            let modifier = locale[dot..].find('@').map(|at| &locale[dot + at..]);
+            format!("{}{}", &locale[..dot], modifier.unwrap_or(""))
+        }
+        None => locale.to_string(),
+    };
+
+    let (base, modifier) = match locale.find('@') {
+        Some(at) => (&locale[..at], Some(&locale[at..])),
+        None => (&locale[..], None),
+    };
+    let (lang, country) = match base.find('_') {
+        Some(underscore) => (&base[..underscore], Some(&base[underscore..])),
+        None => (base, None),
+    };
+
+    let mut candidates = Vec::new();
+    if let (Some(country), Some(modifier)) = (country, modifier) {
+        candidates.push(format!("{}{}{}", lang, country, modifier));
+    }
+    if let Some(country) = country {
+        candidates.push(format!("{}{}", lang, country));
+    }
+    if let Some(modifier) = modifier {
+        candidates.push(format!("{}{}", lang, modifier));
+    }
+    if !lang.is_empty() {
+        candidates.push(lang.to_string());
+    }
+
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{locale_candidates, ThemeIndex};
+
+    const INDEX: &str = "# A comment
+[Icon Theme]
+Name=Breeze
+Name[de]=Brise
+Name[sr@latin]=Briz
+Comment = KDE Plasma\\sCursors
+Comment[pt_BR]=Cursores do KDE
+Example=left_ptr
+Inherits=Adwaita, hicolor,,
+Hidden=false
+Broken line
+
+[Other Section]
+Name=Wrong
+";
+
+    #[test]
+    fn test_parse() {
+        let index = ThemeIndex::parse(INDEX);
+
+        assert_eq!(index.name(), Some("Breeze"));
+        assert_eq!(index.comment(), Some("KDE Plasma Cursors"));
+        assert_eq!(index.example(), Some("left_ptr"));
+        assert_eq!(index.inherits(), vec!["Adwaita", "hicolor"]);
+        assert!(!index.hidden());
+        assert_eq!(index.get("Directories"), None);
+
+        assert_eq!(ThemeIndex::parse("Name=Outside").name(), None);
+        assert_eq!(ThemeIndex::parse("").inherits(), Vec::<String>::new());
+
+        let index = ThemeIndex::parse("[Icon Theme]\nInherits = ,;\t\tAdwaita;;;;hicolor  Tail");
+        assert_eq!(index.inherits(), vec!["Adwaita", "hicolor", "Tail"]);
+    }
+
+    #[test]
+    fn test_localized() {
+        let index = ThemeIndex::parse(INDEX);
+
+        assert_eq!(index.localized_name("de_DE.UTF-8"), Some("Brise"));
+        assert_eq!(index.localized_name("de"), Some("Brise"));
+        assert_eq!(index.localized_name("sr_RS@latin"), Some("Briz"));
+        assert_eq!(index.localized_name("sr_RS"), Some("Breeze"));
+        assert_eq!(index.localized_name("C"), Some("Breeze"));
+        assert_eq!(index.localized_comment("pt_BR"), Some("Cursores do KDE"));
+        assert_eq!(index.localized_comment("pt_PT"), Some("KDE Plasma Cursors"));
+    }
+
+    #[test]
+    fn test_locale_candidates() {
+        assert_eq!(
+            locale_candidates("sr_RS.UTF-8@latin"),
+            vec!["sr_RS@latin", "sr_RS", "sr@latin", "sr"]
+        );
+        assert_eq!(locale_candidates("de_DE"), vec!["de_DE", "de"]);
+        assert_eq!(locale_candidates("fr"), vec!["fr"]);
+    }
+}
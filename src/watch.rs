@@ -0,0 +1,169 @@
+use crate::cache::CursorCache;
+use crate::{CursorTheme, CursorThemeIml};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// The state of a watched path: its modification time and length, or
+/// `None` if it doesn't exist.
+type State = Option<(SystemTime, u64)>;
+
+/// Detects changes to the directories a theme is loaded from, by polling
+/// their modification times.
+///
+/// The watched paths are the search paths, the directories of the theme and
+/// of every theme it inherits from in each search path (including the ones
+/// that don't exist yet, so that installing a theme is noticed), their
+/// `index.theme` files, and their cursor files.
+#[derive(Debug, Clone)]
+pub struct ThemeWatcher {
+    name: String,
+    search_paths: Vec<PathBuf>,
+    snapshot: BTreeMap<PathBuf, State>,
+}
+
+/// The paths that changed since the last poll of a
+/// [`ThemeWatcher`](struct.ThemeWatcher.html).
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ThemeChange {
+    /// The paths that were created, modified or removed, sorted.
+    pub paths: Vec<PathBuf>,
+}
+
+impl ThemeWatcher {
+    /// Start watching the directories the given theme is loaded from.
+    pub fn new(theme: &CursorTheme) -> Self {
+        let name = theme.theme.name.clone();
+        let search_paths = theme.search_paths.clone();
+        let snapshot = snapshot(&name, &search_paths);
+
+        ThemeWatcher {
+            name,
+            search_paths,
+            snapshot,
+        }
+    }
+
+    /// Check the watched paths, returning the ones that changed since the
+    /// watcher was created or last polled, if any.
+    ///
+    /// The inheritance chain is resolved again on each poll, so that the
+    /// themes added to the chain by a change are watched too.
+    pub fn poll(&mut self) -> Option<ThemeChange> {
+        let snapshot = snapshot(&self.name, &self.search_paths);
+
+        let mut paths: Vec<PathBuf> = snapshot
+            .iter()
+            .filter(|&(path, state)| self.snapshot.get(path) != Some(state))
+            .map(|(path, _)| path.clone())
+            .collect();
+        paths.extend(
+            self.snapshot
+                .keys()
+                .filter(|path| !snapshot.contains_key(*path))
+                .cloned(),
+        );
+        paths.sort();
+
+        self.snapshot = snapshot;
+        if paths.is_empty() {
+            None
+        } else {
+            Some(ThemeChange { paths })
+        }
+    }
+}
+
+impl CursorCache {
+    /// Poll the watcher, and [`clear`](#method.clear) the cache if anything
+    /// changed. The change is returned, so that the host can reload the
+    /// cursors it is displaying.
+    pub fn invalidate_if_changed(&mut self, watcher: &mut ThemeWatcher) -> Option<ThemeChange> {
+        let change = watcher.poll()?;
+        self.clear();
+        Some(change)
+    }
+}
+
+/// Record the state of every watched path.
+fn snapshot(name: &str, search_paths: &[PathBuf]) -> BTreeMap<PathBuf, State> {
+    let mut snapshot = BTreeMap::new();
+    let mut record = |path: PathBuf| {
+        let state = state(&path);
+        snapshot.insert(path, state);
+    };
+
+    for search_path in search_paths {
+        record(search_path.clone());
+    }
+
+    for (theme, _) in CursorThemeIml::load(name, search_paths).chain(search_paths) {
+        for search_path in search_paths {
+            let theme_dir = search_path.join(&theme.name);
+            let cursors_dir = theme_dir.join("cursors");
+
+            record(theme_dir.join("index.theme"));
+            if let Ok(entries) = fs::read_dir(&cursors_dir) {
+                for entry in entries.filter_map(Result::ok) {
+                    record(entry.path());
+                }
+            }
+            record(cursors_dir);
+            record(theme_dir);
+        }
+    }
+
+    snapshot
+}
+
+/// Get the state of a path, following symbolic links.
+fn state(path: &Path) -> State {
+    let metadata = fs::metadata(path).ok()?;
+    let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+
+    Some((modified, metadata.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ThemeChange, ThemeWatcher};
+    use crate::cache::CursorCache;
+    use crate::test_util::TempDir;
+    use crate::CursorTheme;
+    use std::fs;
+
+    #[test]
+    fn test_watcher() {
+        let dir = TempDir::new("watcher");
+        let child = dir.add_theme("child", Some("Inherits=parent"), &["x"]);
+
+        let theme = CursorTheme::load_with_search_paths("child", vec![dir.0.clone()]);
+        let mut watcher = ThemeWatcher::new(&theme);
+        let mut cache = CursorCache::new(theme);
+        assert_eq!(watcher.poll(), None);
+        assert_eq!(cache.load_icon("y"), None);
+
+        // Installing the parent theme is noticed, and so is its content.
+        let parent = dir.add_theme("parent", Some("Inherits=none"), &["y"]);
+        let change = cache.invalidate_if_changed(&mut watcher).unwrap();
+        assert!(change.paths.contains(&parent));
+        assert!(change.paths.contains(&parent.join("cursors/y")));
+        assert_eq!(cache.load_icon("y"), Some(parent.join("cursors/y")));
+        assert_eq!(cache.invalidate_if_changed(&mut watcher), None);
+
+        fs::remove_file(child.join("cursors/x")).unwrap();
+        fs::write(child.join("cursors/z"), b"").unwrap();
+        let change = watcher.poll().unwrap();
+        assert!(change.paths.contains(&child.join("cursors/x")));
+        assert!(change.paths.contains(&child.join("cursors/z")));
+
+        fs::write(parent.join("cursors/y"), b"longer").unwrap();
+        assert_eq!(
+            watcher.poll(),
+            Some(ThemeChange {
+                paths: vec![parent.join("cursors/y")],
+            })
+        );
+    }
+}